@@ -2,7 +2,19 @@ import os from 'node:os'
 
 import test from 'ava'
 
-import { Entry, findCredentials, findCredentialsAsync, AsyncEntry } from '../index'
+import { InvalidError, NoEntryError, NotSupportedError } from '../errors'
+import {
+  Entry,
+  findCredentials,
+  findCredentialsAsync,
+  searchCredentials,
+  AsyncEntry,
+  setLenientErrors,
+  createCollection,
+  deleteCollection,
+  getCollectionByAlias,
+  listCollections,
+} from '../index'
 
 const testPassword = 'napi.rs'
 const testService = 'keyring-node-test-service'
@@ -29,6 +41,41 @@ test('Should create and get password back async', async (t) => {
   await t.notThrowsAsync(() => entry.deleteCredential())
 })
 
+test('Should find credentials of dotted services by service and user', (t) => {
+  const service = 'https://registry.keyring-node.example'
+  const users = ['alice', 'bob.smith']
+  const entries = users.map((user) => new Entry(service, user))
+  t.teardown(() => entries.forEach((entry) => entry.deleteCredential()))
+  entries.forEach((entry, index) => entry.setPassword(`${testPassword}-${index}`))
+  t.deepEqual(
+    findCredentials(service)
+      .map(({ account, password }) => [account, password])
+      .sort(),
+    [
+      ['alice', `${testPassword}-0`],
+      ['bob.smith', `${testPassword}-1`],
+    ],
+  )
+  t.deepEqual(
+    searchCredentials({ service, user: 'bob.smith' }).map((entry) => [entry.service, entry.user]),
+    [[service, 'bob.smith']],
+  )
+})
+
+test('Should compare conditional writes with passwords written by setPassword', async (t) => {
+  const entry = new Entry(testService, 'test-user-conditional')
+  t.teardown(() => entry.deleteCredential())
+  entry.setPassword(testPassword)
+  t.false(entry.setPasswordIf('stale', 'second'))
+  t.true(entry.setPasswordIf(testPassword, 'second'))
+  t.is(entry.getPassword(), 'second')
+  const asyncEntry = new AsyncEntry(testService, 'test-user-conditional')
+  t.true(await asyncEntry.setPasswordIf('second', 'third'))
+  entry.setPassword('fourth')
+  t.true(entry.setPasswordIf('fourth', 'fifth'))
+  t.is(await asyncEntry.getPassword(), 'fifth')
+})
+
 test('Should create and set secret', (t) => {
   const entry = new Entry(testService, testUser)
   t.notThrows(() => entry.setSecret(testSecret))
@@ -72,6 +119,31 @@ test('Should handle binary data correctly with setSecret/getSecret async', async
   await t.notThrowsAsync(() => entry.deleteCredential())
 })
 
+test('Should throw NoEntry for missing credentials', (t) => {
+  const entry = new Entry(testService, 'missing-user')
+  const error = t.throws(() => entry.getPassword(), { instanceOf: NoEntryError })
+  t.is(error?.code, 'NoEntry')
+  t.throws(() => entry.getSecret(), { instanceOf: NoEntryError })
+  t.throws(() => entry.deleteCredential(), { instanceOf: NoEntryError })
+})
+
+test('Should reject with NoEntry for missing credentials async', async (t) => {
+  const entry = new AsyncEntry(testService, 'missing-user')
+  const error = await t.throwsAsync(() => entry.getPassword(), { instanceOf: NoEntryError })
+  t.is(error?.code, 'NoEntry')
+  await t.throwsAsync(() => entry.getSecret(), { instanceOf: NoEntryError })
+  await t.throwsAsync(() => entry.deleteCredential(), { instanceOf: NoEntryError })
+})
+
+test('Should swallow errors when lenient errors are enabled', async (t) => {
+  setLenientErrors(true)
+  t.teardown(() => setLenientErrors(false))
+  const entry = new Entry(testService, 'missing-user')
+  t.is(entry.getPassword(), null)
+  t.false(entry.deleteCredential())
+  t.is(await new AsyncEntry(testService, 'missing-user').getPassword(), null)
+})
+
 let testTarget: string | undefined
 
 const platform = os.platform()
@@ -118,3 +190,23 @@ if (testTarget && !(process.env.CI && (platform === 'linux' || platform === 'fre
     t.fail()
   })
 }
+
+if (platform === 'linux' || platform === 'freebsd') {
+  if (!process.env.CI) {
+    test('Should create, list and delete Secret Service collections', (t) => {
+      const label = 'keyring-node-test-collection'
+      const created = createCollection(label)
+      t.is(created.label, label)
+      t.false(created.isDefault)
+      t.throws(() => createCollection(label), { instanceOf: InvalidError })
+      t.true(listCollections().some((collection) => collection.path === created.path))
+      t.true(getCollectionByAlias()?.isDefault)
+      t.true(deleteCollection(label))
+      t.throws(() => deleteCollection(label), { instanceOf: NoEntryError })
+    })
+  }
+} else {
+  test('Should only manage collections on the Secret Service', (t) => {
+    t.throws(() => listCollections(), { instanceOf: NotSupportedError })
+  })
+}
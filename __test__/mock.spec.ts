@@ -0,0 +1,410 @@
+import test from 'ava'
+
+import {
+  AbortedError,
+  AmbiguousError,
+  InvalidError,
+  NoEntryError,
+  NoStorageAccessError,
+  NotSupportedError,
+  PlatformFailureError,
+  TimeoutError,
+} from '../errors'
+import {
+  AsyncEntry,
+  Entry,
+  configureStore,
+  deleteCredentials,
+  deleteCredentialsAsync,
+  findCredentials,
+  findCredentialsAsync,
+  getMockStore,
+  getPasswords,
+  getPasswordsAsync,
+  listAccounts,
+  listAccountsAsync,
+  searchCredentials,
+  searchCredentialsAsync,
+  setPasswords,
+  setPasswordsAsync,
+} from '../index'
+
+test.beforeEach(() => {
+  configureStore({ backend: 'mock' })
+})
+
+test('Should expose the credentials written by entries', (t) => {
+  const store = getMockStore()
+  new Entry('keyring-node-mock-test', 'test-user').setPassword('napi.rs')
+  Entry.withTarget('work', 'keyring-node-mock-test', 'test-user').setPassword('work')
+  const credentials = store.credentials()
+  t.is(credentials.length, 2)
+  t.deepEqual(Buffer.from(credentials.find((c) => c.target === 'work')!.secret).toString(), 'work')
+})
+
+test('Should expose and round-trip entry specifiers', async (t) => {
+  const entry = Entry.withTarget('work', 'keyring-node-mock-specifier', 'test-user')
+  t.is(entry.service, 'keyring-node-mock-specifier')
+  t.is(entry.user, 'test-user')
+  t.is(entry.target, 'work')
+  t.is(new Entry('keyring-node-mock-specifier', 'test-user').target, null)
+  const specifier = JSON.parse(JSON.stringify(entry))
+  t.deepEqual(specifier, { service: 'keyring-node-mock-specifier', user: 'test-user', target: 'work' })
+  entry.setPassword('napi.rs')
+  t.is(await AsyncEntry.fromSpecifier(specifier).getPassword(), 'napi.rs')
+  t.deepEqual(searchCredentials({ service: 'keyring-node-mock-specifier' })[0].toJSON(), specifier)
+})
+
+test('Should create the same entry for every spelling of a URL', (t) => {
+  Entry.fromUrl('https://alice@registry.example.com').setPassword('napi.rs')
+  t.is(Entry.fromUrl('HTTPS://alice@Registry.Example.com:443/').getPassword(), 'napi.rs')
+  t.is(Entry.fromUrl('https://registry.example.com/?tab=1', { user: 'alice' }).getPassword(), 'napi.rs')
+  const entry = Entry.fromUrl('ssh://git@example.com:2222//org/repo/', { granularity: 'path' })
+  t.is(entry.service, 'ssh://example.com:2222/org/repo')
+  t.is(entry.user, 'git')
+  t.is(Entry.fromUrl('https://git@example.com:8443', { granularity: 'host' }).service, 'example.com')
+  t.throws(() => Entry.fromUrl('https://example.com'), { instanceOf: InvalidError })
+})
+
+test('Should restore a snapshot', (t) => {
+  const store = getMockStore()
+  const entry = new Entry('keyring-node-mock-test', 'test-user')
+  entry.setPassword('before')
+  const snapshot = store.snapshot()
+  entry.setPassword('after')
+  store.restore(snapshot)
+  t.is(entry.getPassword(), 'before')
+  store.clear()
+  t.deepEqual(store.credentials(), [])
+})
+
+test('Should fail the next matching operation', async (t) => {
+  const store = getMockStore()
+  const entry = new Entry('keyring-node-mock-test', 'test-user')
+  entry.setPassword('napi.rs')
+  store.failNext('get', 'NoStorageAccess', { service: 'keyring-node-mock-test' })
+  t.throws(() => entry.getPassword(), { instanceOf: NoStorageAccessError })
+  t.is(entry.getPassword(), 'napi.rs')
+  store.failNext('set', 'PlatformFailure', { times: 2 })
+  t.throws(() => entry.setPassword('a'), { instanceOf: PlatformFailureError })
+  await t.throwsAsync(new AsyncEntry('keyring-node-mock-test', 'test-user').setPassword('b'), {
+    instanceOf: PlatformFailureError,
+  })
+  store.failNext('delete', 'Ambiguous')
+  t.throws(() => entry.deleteCredential(), { instanceOf: AmbiguousError })
+  t.true(entry.deleteCredential())
+})
+
+test('Should find credentials in the active store', async (t) => {
+  new Entry('keyring-node-mock-find', 'alice').setPassword('a')
+  new Entry('keyring-node-mock-find', 'bob').setPassword('b')
+  new Entry('keyring-node-mock-other', 'carol').setPassword('c')
+  t.deepEqual(
+    findCredentials('keyring-node-mock-find')
+      .map(({ account, password }) => [account, password])
+      .sort(),
+    [
+      ['alice', 'a'],
+      ['bob', 'b'],
+    ],
+  )
+  const [carol] = await findCredentialsAsync('keyring-node-mock-other')
+  t.is(carol.account, 'carol')
+  t.deepEqual(carol.secret, new Uint8Array([0x63]))
+  getMockStore().failNext('search', 'NoStorageAccess')
+  t.throws(() => findCredentials('keyring-node-mock-find'), { instanceOf: NoStorageAccessError })
+})
+
+test('Should describe found credentials', (t) => {
+  const before = Date.now()
+  Entry.withTarget('work', 'keyring-node-mock-record', 'test-user').setPassword('napi.rs')
+  getMockStore().restore(
+    getMockStore()
+      .snapshot()
+      .map((credential) => ({ ...credential, attributes: { note: 'hello' } })),
+  )
+  const [credential] = findCredentials('keyring-node-mock-record')
+  t.is(credential.service, 'keyring-node-mock-record')
+  t.is(credential.target, 'work')
+  t.deepEqual(credential.attributes, { note: 'hello' })
+  t.false(credential.locked)
+  t.true(credential.created! >= before)
+  t.is(credential.modified, credential.created)
+})
+
+test('Should read and update attributes', async (t) => {
+  const entry = new Entry('keyring-node-mock-attributes', 'test-user')
+  t.throws(() => entry.getAttributes(), { code: 'NoEntry' })
+  t.throws(() => entry.updateAttributes({ note: 'hello' }), { code: 'NoEntry' })
+  entry.setPassword('napi.rs')
+  entry.updateAttributes({ note: 'hello' })
+  t.deepEqual(entry.getAttributes(), { note: 'hello' })
+  const asyncEntry = new AsyncEntry('keyring-node-mock-attributes', 'test-user')
+  await asyncEntry.updateAttributes({ note: 'updated', color: 'blue' })
+  t.deepEqual(await asyncEntry.getAttributes(), { note: 'updated', color: 'blue' })
+  t.throws(() => entry.updateAttributes({ user: 'other' }), { instanceOf: InvalidError })
+  await t.throwsAsync(asyncEntry.updateAttributes({ service: 'other' }), { instanceOf: InvalidError })
+})
+
+test('Should check whether a credential exists without reading it', async (t) => {
+  const store = getMockStore()
+  const entry = new Entry('keyring-node-mock-exists', 'test-user')
+  t.is(entry.exists(), 'absent')
+  entry.setPassword('napi.rs')
+  t.is(entry.exists(), 'present')
+  store.restore(
+    store
+      .snapshot()
+      .map((credential) => ({ ...credential, locked: credential.service === 'keyring-node-mock-exists' })),
+  )
+  t.is(await new AsyncEntry('keyring-node-mock-exists', 'test-user').exists(), 'locked')
+})
+
+test('Should report and resolve ambiguous credentials', async (t) => {
+  const store = getMockStore()
+  const service = 'keyring-node-mock-ambiguous'
+  const entry = new Entry(service, 'test-user')
+  entry.setPassword('first')
+  const others = store.snapshot().filter((credential) => credential.service !== service)
+  const credential = store.snapshot().find((credential) => credential.service === service)!
+  const secret = (password: string) => new Uint8Array(Buffer.from(password))
+  store.restore([
+    ...others,
+    credential,
+    { ...credential, secret: secret('newest'), created: credential.created! + 1000 },
+    { ...credential, secret: secret('oldest'), created: credential.created! - 1000 },
+  ])
+  const error = t.throws(() => entry.getPassword(), { instanceOf: AmbiguousError })
+  t.deepEqual(
+    error?.credentials.map((credential) => credential.getPassword()),
+    ['first', 'newest', 'oldest'],
+  )
+  t.is(entry.exists(), 'ambiguous')
+  t.is(entry.resolveAmbiguity({ keep: 'newest' }), 2)
+  t.is(entry.getPassword(), 'newest')
+  t.is(entry.resolveAmbiguity({ keep: 'oldest' }), 0)
+  store.restore([...others, credential, { ...credential, secret: secret('second') }])
+  const asyncEntry = new AsyncEntry(service, 'test-user')
+  await t.throwsAsync(asyncEntry.resolveAmbiguity({ keep: 2 }), { instanceOf: InvalidError })
+  t.is(await asyncEntry.resolveAmbiguity({ keep: 1 }), 1)
+  t.is(await asyncEntry.getPassword(), 'second')
+})
+
+test('Should only write when the current password matches', async (t) => {
+  const entry = new Entry('keyring-node-mock-cas', 'test-user')
+  t.true(entry.setSecretIfAbsent(Buffer.from('first')))
+  t.false(entry.setSecretIfAbsent(Buffer.from('second')))
+  t.false(entry.setPasswordIf('stale', 'second'))
+  t.true(entry.setPasswordIf('first', 'second'))
+  t.is(entry.getPassword(), 'second')
+  const asyncEntry = new AsyncEntry('keyring-node-mock-cas', 'test-user')
+  t.false(await asyncEntry.setPasswordIf(null, 'third'))
+  t.true(await asyncEntry.setPasswordIf('second', 'third'))
+  t.is(await asyncEntry.getPassword(), 'third')
+  t.true(entry.setPasswordIf('third', 'fourth', { ttlSeconds: 60 }))
+  t.true(entry.getExpiry()! > 0)
+  t.true(await asyncEntry.setPasswordIf('fourth', 'fifth'))
+  t.is(entry.getExpiry(), null)
+})
+
+test('Should expire credentials written with a TTL', async (t) => {
+  const entry = new Entry('keyring-node-mock-ttl', 'test-user')
+  entry.setPassword('napi.rs', { ttlSeconds: 60 })
+  const expiry = entry.getExpiry()
+  t.true(expiry! > 0 && expiry! <= 60)
+  entry.setPassword('napi.rs')
+  t.is(entry.getExpiry(), null)
+  await new AsyncEntry('keyring-node-mock-ttl', 'test-user').setPassword('napi.rs', undefined, { ttlSeconds: 60 })
+  t.false('expires-at' in entry.getAttributes()!)
+  entry.updateAttributes({ 'expires-at': '1' })
+  t.deepEqual(findCredentials('keyring-node-mock-ttl'), [])
+  t.is(entry.exists(), 'absent')
+  const stored = () => getMockStore().credentials().filter((c) => c.service === 'keyring-node-mock-ttl')
+  // Only a read of the entry deletes the expired credential.
+  t.is(stored().length, 1)
+  t.throws(() => entry.getPassword(), { instanceOf: NoEntryError })
+  t.is(stored().length, 0)
+  t.throws(() => entry.setPassword('napi.rs', { ttlSeconds: 0 }), { instanceOf: InvalidError })
+})
+
+test('Should split secrets that are too long for the store', async (t) => {
+  const store = getMockStore()
+  store.setMaxSecretLength(256)
+  const service = 'keyring-node-mock-chunks'
+  const entry = new Entry(service, 'test-user')
+  const password = 'napi.rs '.repeat(100)
+  entry.setPassword(password)
+  t.is(entry.getPassword(), password)
+  t.is(await new AsyncEntry(service, 'test-user').getPassword(), password)
+  t.true(store.credentials().every((credential) => credential.secret.length <= 256))
+  t.deepEqual(
+    findCredentials(service).map((credential) => [credential.account, credential.password]),
+    [['test-user', password]],
+  )
+  store.restore(
+    store
+      .snapshot()
+      .map((credential) => (credential.user === 'test-user' ? credential : { ...credential, secret: new Uint8Array([0]) })),
+  )
+  t.throws(() => entry.getPassword(), { code: 'BadEncoding' })
+  entry.setPassword('short')
+  store.failNext('get', 'NoStorageAccess')
+  entry.setPassword('shorter')
+  t.throws(() => entry.getPassword(), { code: 'NoStorageAccess' })
+  t.is(store.credentials().filter((credential) => credential.service === service).length, 1)
+  t.deepEqual(entry.getAttributes(), {})
+  const manifest = 'napi-keyring-chunks/v1 0 1 5 0'
+  entry.setPassword(manifest)
+  t.is(entry.getPassword(), manifest)
+  t.true(entry.deleteCredential())
+  t.is(store.credentials().filter((credential) => credential.service === service).length, 0)
+})
+
+test('Should run async operations in the order they were started', async (t) => {
+  const entry = new AsyncEntry('keyring-node-mock-worker', 'test-user')
+  const [, , password] = await Promise.all([entry.setPassword('first'), entry.setPassword('second'), entry.getPassword()])
+  t.is(password, 'second')
+})
+
+test('Should time out slow operations', async (t) => {
+  const store = getMockStore()
+  const entry = new AsyncEntry('keyring-node-mock-timeout', 'test-user')
+  await entry.setPassword('napi.rs')
+  store.delayNext('get', 1000)
+  await t.throwsAsync(entry.getPassword(null, { timeoutMs: 20 }), { instanceOf: TimeoutError })
+  t.is(await entry.getPassword(null, { timeoutMs: 500 }), 'napi.rs')
+  store.delayNext('search', 1000)
+  await t.throwsAsync(findCredentialsAsync('keyring-node-mock-timeout', null, null, { timeoutMs: 20 }), {
+    instanceOf: TimeoutError,
+  })
+  store.delayNext('search', 1000)
+  await t.throwsAsync(listAccountsAsync('keyring-node-mock-timeout', null, null, { timeoutMs: 20 }), {
+    instanceOf: TimeoutError,
+  })
+})
+
+test('Should abort running and queued operations', async (t) => {
+  const store = getMockStore()
+  const entry = new AsyncEntry('keyring-node-mock-abort', 'test-user')
+  store.delayNext('set', 1000)
+  const controller = new AbortController()
+  const running = entry.setPassword('first', controller.signal)
+  const queued = entry.setPassword('second', controller.signal)
+  controller.abort()
+  await t.throwsAsync(running, { instanceOf: AbortedError })
+  await t.throwsAsync(queued, { instanceOf: AbortedError })
+  t.throws(() => store.failNext('get', 'Timeout'), { instanceOf: InvalidError })
+  t.throws(() => store.failNext('get', 'NoEntry', { times: 0 }), { instanceOf: InvalidError })
+})
+
+test('Should read, write and delete credentials in batches', async (t) => {
+  const service = 'keyring-node-mock-batch'
+  t.deepEqual(
+    setPasswords([
+      { service, user: 'first', password: 'one' },
+      { service, user: 'second', target: 'work', password: 'two' },
+    ]).map((result) => result.error),
+    [undefined, undefined],
+  )
+  await setPasswordsAsync([{ service, user: 'third', password: 'three' }])
+  getMockStore().failNext('get', 'NoStorageAccess', { user: 'second' })
+  const passwords = getPasswords([
+    { service, user: 'first' },
+    { service, user: 'second', target: 'work' },
+    { service, user: 'missing' },
+  ])
+  t.is(passwords[0].password, 'one')
+  t.is(passwords[1].error?.code, 'NoStorageAccess')
+  t.is(passwords[2].error?.code, 'NoEntry')
+  const [third] = await getPasswordsAsync([{ service, user: 'third' }])
+  t.is(third.password, 'three')
+  const expiring = new Entry(service, 'third')
+  expiring.setPassword('three', { ttlSeconds: 60 })
+  setPasswords([{ service, user: 'third', password: 'four' }])
+  t.is(expiring.getExpiry(), null)
+  expiring.updateAttributes({ 'expires-at': '1' })
+  t.is(getPasswords([{ service, user: 'third' }])[0].error?.code, 'NoEntry')
+  const deleted = await deleteCredentialsAsync([
+    { service, user: 'first' },
+    { service, user: 'second', target: 'work' },
+  ])
+  t.deepEqual(
+    deleted.map((result) => result.error),
+    [undefined, undefined],
+  )
+  t.is(deleteCredentials([{ service, user: 'first' }])[0].error?.code, 'NoEntry')
+})
+
+test('Should search credentials by any combination of fields', async (t) => {
+  new Entry('keyring-node-mock-search', 'alice').setPassword('a')
+  Entry.withTarget('work', 'keyring-node-mock-search', 'alice').setPassword('w')
+  new Entry('keyring-node-mock-search', 'bob').setPassword('b')
+  getMockStore().restore(
+    getMockStore()
+      .snapshot()
+      .map((credential) => (credential.user === 'bob' ? { ...credential, attributes: { team: 'infra' } } : credential)),
+  )
+  t.is(searchCredentials({ service: 'keyring-node-mock-search' }).length, 3)
+  t.is(searchCredentials({ user: 'alice' }).length, 2)
+  const [work] = searchCredentials({ user: 'alice', target: 'work' })
+  t.is(work.getPassword(), 'w')
+  const [bob] = await searchCredentialsAsync({ attributes: { team: 'infra' } })
+  t.is(await bob.getPassword(), 'b')
+  t.deepEqual(searchCredentials({ service: 'keyring-node-mock-search', user: 'carol' }), [])
+})
+
+test('Should list accounts without reading secrets', async (t) => {
+  const store = getMockStore()
+  new Entry('keyring-node-mock-list', 'alice').setPassword('a')
+  store.failNext('get', 'NoStorageAccess')
+  const [withoutSecret] = findCredentials('keyring-node-mock-list', null, { includeSecrets: false })
+  t.is(withoutSecret.secret, undefined)
+  t.is(withoutSecret.error, undefined)
+  const [account] = listAccounts('keyring-node-mock-list')
+  t.is(account.account, 'alice')
+  // The injected failure is only consumed once the secret is read.
+  t.throws(() => account.entry.getPassword(), { instanceOf: NoStorageAccessError })
+  t.is(account.entry.getPassword(), 'a')
+  const [asyncAccount] = await listAccountsAsync('keyring-node-mock-list')
+  t.is(await asyncAccount.entry.getPassword(), 'a')
+})
+
+test('Should skip, report or unlock locked credentials', (t) => {
+  const store = getMockStore()
+  new Entry('keyring-node-mock-locked', 'open').setPassword('o')
+  new Entry('keyring-node-mock-locked', 'closed').setPassword('c')
+  store.restore(store.snapshot().map((credential) => ({ ...credential, locked: credential.user === 'closed' })))
+  const summary = (options?: Parameters<typeof findCredentials>[2]) =>
+    findCredentials('keyring-node-mock-locked', null, options)
+      .map(({ account, locked, password, error }) => [account, locked, password, error?.code])
+      .sort()
+  t.deepEqual(summary(), [
+    ['closed', true, undefined, undefined],
+    ['open', false, 'o', undefined],
+  ])
+  t.deepEqual(summary({ locked: 'skip' }), [['open', false, 'o', undefined]])
+  store.failNext('unlock', 'NoStorageAccess')
+  t.deepEqual(summary({ locked: 'unlock', promptTimeoutSeconds: 1 }), [
+    ['closed', true, undefined, 'NoStorageAccess'],
+    ['open', false, 'o', undefined],
+  ])
+  t.deepEqual(summary({ locked: 'unlock' }), [
+    ['closed', false, 'c', undefined],
+    ['open', false, 'o', undefined],
+  ])
+})
+
+test('Should report credentials that are not valid UTF-8', (t) => {
+  const secret = new Uint8Array([0xff, 0xfe])
+  new Entry('keyring-node-mock-binary', 'test-user').setSecret(secret)
+  const [credential] = findCredentials('keyring-node-mock-binary')
+  t.deepEqual(credential.secret, secret)
+  t.is(credential.password, undefined)
+  t.is(credential.error?.code, 'BadEncoding')
+})
+
+test('Should only be available when the mock backend is active', (t) => {
+  t.notThrows(() => getMockStore())
+  configureStore()
+  t.throws(() => getMockStore(), { instanceOf: NotSupportedError })
+})
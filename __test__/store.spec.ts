@@ -0,0 +1,69 @@
+import { spawnSync } from 'node:child_process'
+import os from 'node:os'
+import path from 'node:path'
+
+import test from 'ava'
+
+import { NotSupportedError } from '../errors'
+import { AsyncEntry, Entry, configureStore, getStoreInfo } from '../index'
+
+test('Should throw when the requested backend is not available', (t) => {
+  const backend = os.platform() === 'darwin' ? 'windows' : 'keychain'
+  t.throws(() => configureStore({ backend }), { instanceOf: NotSupportedError })
+})
+
+test('Should use the configured backend', async (t) => {
+  configureStore({ backend: 'mock' })
+  const entry = new Entry('keyring-node-store-test', 'test-user')
+  entry.setPassword('napi.rs')
+  t.is(await new AsyncEntry('keyring-node-store-test', 'test-user').getPassword(), 'napi.rs')
+  entry.deleteCredential()
+})
+
+test('Should report the active store', (t) => {
+  configureStore({ backend: 'mock' })
+  const info = getStoreInfo()
+  t.is(info.backend, 'mock')
+  t.is(info.persistence, 'process-only')
+  t.true(info.vendor.startsWith('Mock store'))
+  t.is(info.fallbackReason, undefined)
+  t.is(info.keyringSerial, undefined)
+})
+
+test('Should only manage permissions of keyutils keys', (t) => {
+  configureStore({ backend: 'mock' })
+  t.throws(() => new Entry('keyring-node-store-test', 'test-user').getPermissions(), {
+    instanceOf: NotSupportedError,
+  })
+})
+
+if (os.platform() === 'linux') {
+  test('Should share a read-only keyutils key with child processes', (t) => {
+    configureStore({ backend: 'keyutils', options: { keyring: 'process' } })
+    const entry = new Entry('keyring-node-share-test', 'worker')
+    entry.setPassword('token')
+    t.teardown(() => entry.deleteCredential())
+    entry.shareWithChildren({ permissions: { possessor: ['view', 'read', 'search'], user: ['view'] } })
+    t.deepEqual(entry.getPermissions()?.possessor, ['view', 'read', 'search'])
+
+    const child = (code: string) =>
+      spawnSync(
+        process.execPath,
+        [
+          '-e',
+          `const { Entry, configureStore } = require(${JSON.stringify(path.join(process.cwd(), 'index.js'))})
+          configureStore({ backend: 'keyutils' })
+          const entry = new Entry('keyring-node-share-test', 'worker')
+          ${code}`,
+        ],
+        { encoding: 'utf8' },
+      ).stdout.trim()
+    t.is(child('console.log(entry.getPassword())'), 'token')
+    t.is(child(`try { entry.setPassword('changed') } catch (err) { console.log(err.code) }`), 'NoStorageAccess')
+    t.is(entry.getPassword(), 'token')
+  })
+} else {
+  test.skip('Skip sharing keyutils keys, which only exist on Linux', (t) => {
+    t.pass()
+  })
+}
@@ -0,0 +1,64 @@
+import type { Entry, ErrorCode } from './index'
+
+/**
+ * Base class of every error thrown or rejected by `@napi-rs/keyring`.
+ *
+ * `instanceof` checks are based on the `code` property set by the native binding.
+ */
+export declare class KeyringError extends Error {
+  readonly code: ErrorCode
+  /** The underlying platform error, if any. */
+  readonly cause?: Error
+}
+
+/** There is no credential in the store that matches the entry. */
+export declare class NoEntryError extends KeyringError {
+  readonly code: 'NoEntry'
+}
+
+/** More than one credential in the store matches the entry. */
+export declare class AmbiguousError extends KeyringError {
+  readonly code: 'Ambiguous'
+  /** An entry for every matching credential, see `Entry.resolveAmbiguity`. */
+  readonly credentials: Entry[]
+}
+
+/** The store could not be accessed, e.g. because it is locked. */
+export declare class NoStorageAccessError extends KeyringError {
+  readonly code: 'NoStorageAccess'
+}
+
+/** The underlying platform store failed. */
+export declare class PlatformFailureError extends KeyringError {
+  readonly code: 'PlatformFailure'
+}
+
+/** The stored data could not be decoded. */
+export declare class BadEncodingError extends KeyringError {
+  readonly code: 'BadEncoding'
+}
+
+/** An attribute exceeded the platform length limit. */
+export declare class TooLongError extends KeyringError {
+  readonly code: 'TooLong'
+}
+
+/** An argument was rejected by the store. */
+export declare class InvalidError extends KeyringError {
+  readonly code: 'Invalid'
+}
+
+/** The operation is not supported by the active store. */
+export declare class NotSupportedError extends KeyringError {
+  readonly code: 'NotSupported'
+}
+
+/** An async operation did not finish within its `timeoutMs`. */
+export declare class TimeoutError extends KeyringError {
+  readonly code: 'Timeout'
+}
+
+/** An async operation was cancelled through its `AbortSignal`. */
+export declare class AbortedError extends KeyringError {
+  readonly code: 'Aborted'
+}
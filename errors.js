@@ -0,0 +1,39 @@
+const codes = [
+  'NoEntry',
+  'Ambiguous',
+  'NoStorageAccess',
+  'PlatformFailure',
+  'BadEncoding',
+  'TooLong',
+  'Invalid',
+  'NotSupported',
+  'Timeout',
+  'Aborted',
+]
+
+// Errors are created by the native binding, so class membership is decided by their `code`.
+class KeyringError extends Error {
+  static [Symbol.hasInstance](error) {
+    return error instanceof Error && codes.includes(error.code)
+  }
+}
+
+function defineError(code) {
+  return class extends KeyringError {
+    static [Symbol.hasInstance](error) {
+      return error instanceof Error && error.code === code
+    }
+  }
+}
+
+module.exports.KeyringError = KeyringError
+module.exports.NoEntryError = defineError('NoEntry')
+module.exports.AmbiguousError = defineError('Ambiguous')
+module.exports.NoStorageAccessError = defineError('NoStorageAccess')
+module.exports.PlatformFailureError = defineError('PlatformFailure')
+module.exports.BadEncodingError = defineError('BadEncoding')
+module.exports.TooLongError = defineError('TooLong')
+module.exports.InvalidError = defineError('Invalid')
+module.exports.NotSupportedError = defineError('NotSupported')
+module.exports.TimeoutError = defineError('Timeout')
+module.exports.AbortedError = defineError('Aborted')
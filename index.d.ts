@@ -13,6 +13,28 @@ export declare class AsyncEntry {
    * The default credential builder is used.
    */
   static withTarget(target: string, service: string, username: string): AsyncEntry
+  /** Create an entry from the specifier returned by `toJSON`, see `Entry.fromSpecifier`. */
+  static fromSpecifier(specifier: EntrySpecifier): AsyncEntry
+  /** Create an entry for a URL, see `Entry.fromUrl`. */
+  static fromUrl(url: string, options?: UrlOptions | undefined | null): AsyncEntry
+  /**
+   * The service of this entry.
+   *
+   * `null` for the handles of an `Ambiguous` error on stores that cannot
+   * tell which service a credential belongs to.
+   */
+  get service(): string | null
+  /** The user of this entry, `null` when the `service` is. */
+  get user(): string | null
+  /** The target of this entry, if it has one. */
+  get target(): string | null
+  /**
+   * The specifier of this entry, to be stored or sent to another thread and
+   * turned back into an entry with `fromSpecifier`.
+   *
+   * `null` when the `service` is.
+   */
+  toJSON(): EntrySpecifier | null
   /**
    * Set the password for this entry.
    *
@@ -21,8 +43,10 @@ export declare class AsyncEntry {
    * that matches this entry.  This can only happen
    * on some platforms, and then only if a third-party
    * application wrote the ambiguous credential.
+   *
+   * With `ttlSeconds`, the credential expires, see `Entry.setPassword`.
    */
-  setPassword(password: string, signal?: AbortSignal | undefined | null): Promise<void>
+  setPassword(password: string, signal?: AbortSignal | undefined | null, options?: AsyncSetOptions | undefined | null): Promise<void>
   /**
    * Set the secret for this entry.
    *
@@ -31,8 +55,25 @@ export declare class AsyncEntry {
    * that matches this entry.  This can only happen
    * on some platforms, and then only if a third-party
    * application wrote the ambiguous credential.
+   *
+   * Takes the same options as `setPassword`.
+   */
+  setSecret(secret: Uint8Array, signal?: AbortSignal | undefined | null, options?: AsyncSetOptions | undefined | null): Promise<void>
+  /**
+   * Set the password for this entry if its current password is `expected`,
+   * or if there is no credential when `expected` is `null`.
+   *
+   * Resolves to whether the password was written, see `Entry.setPasswordIf`.
+   * Takes the same options as `setPassword`.
    */
-  setSecret(secret: Uint8Array, signal?: AbortSignal | undefined | null): Promise<void>
+  setPasswordIf(expected: string | undefined | null, next: string, signal?: AbortSignal | undefined | null, options?: AsyncSetOptions | undefined | null): Promise<boolean>
+  /**
+   * Set the secret for this entry unless there is a credential already.
+   *
+   * Resolves to whether the secret was written, see `Entry.setPasswordIf`.
+   * Takes the same options as `setPassword`.
+   */
+  setSecretIfAbsent(secret: Uint8Array, signal?: AbortSignal | undefined | null, options?: AsyncSetOptions | undefined | null): Promise<boolean>
   /**
    * Retrieve the password saved for this entry.
    *
@@ -44,7 +85,7 @@ export declare class AsyncEntry {
    * on some platforms, and then only if a third-party
    * application wrote the ambiguous credential.
    */
-  getPassword(signal?: AbortSignal | undefined | null): Promise<string | undefined>
+  getPassword(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<string | undefined>
   /**
    * Retrieve the secret saved for this entry.
    *
@@ -56,7 +97,40 @@ export declare class AsyncEntry {
    * on some platforms, and then only if a third-party
    * application wrote the ambiguous credential.
    */
-  getSecret(signal?: AbortSignal | undefined | null): Promise<Uint8Array | undefined>
+  getSecret(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Uint8Array | undefined>
+  /** Check whether there is a credential for this entry, see `Entry.exists`. */
+  exists(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<CredentialState>
+  /** The number of seconds until this entry's credential expires, see `Entry.getExpiry`. */
+  getExpiry(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<number | undefined>
+  /**
+   * Retrieve the attributes the store keeps with this entry's credential.
+   *
+   * Secret Service items include their `label`, and keyutils keys, which
+   * have no attributes, report their `description`.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+   */
+  getAttributes(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Record<string, string> | undefined>
+  /**
+   * Update the attributes the store keeps with this entry's credential.
+   *
+   * Attributes that identify the credential, such as the service, user and
+   * target, cannot be updated and are rejected with an `Invalid` error, as
+   * is any attribute of a keyutils key. On the Secret Service, `label`
+   * updates the item's label.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+   */
+  updateAttributes(attributes: Record<string, string>, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<void>
+  /** Retrieve the permissions of this entry's keyutils key, see `Entry.getPermissions`. */
+  getPermissions(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<KeyPermissions | undefined>
+  /** Set the permissions of this entry's keyutils key, see `Entry.setPermissions`. */
+  setPermissions(permissions: KeyPermissions, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<void>
+  /**
+   * Link this entry's keyutils key to the session keyring, which child
+   * processes inherit, see `Entry.shareWithChildren`.
+   */
+  shareWithChildren(shareOptions?: ShareOptions | undefined | null, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<number>
   /**
    * Delete the underlying credential for this entry.
    *
@@ -72,9 +146,14 @@ export declare class AsyncEntry {
    * structure, which is controlled by Rust.  It only
    * affects the underlying credential store.
    */
-  deleteCredential(signal?: AbortSignal | undefined | null): Promise<boolean>
+  deleteCredential(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<boolean>
+  /**
+   * Delete every credential matching this entry but the one to `keep`,
+   * see `Entry.resolveAmbiguity`.
+   */
+  resolveAmbiguity(options: ResolveOptions, signal?: AbortSignal | undefined | null, asyncOptions?: AsyncOptions | undefined | null): Promise<number>
   /** Alias for `deleteCredential` */
-  deletePassword(signal?: AbortSignal | undefined | null): Promise<unknown>
+  deletePassword(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<boolean>
 }
 
 export declare class Entry {
@@ -90,6 +169,38 @@ export declare class Entry {
    * The default credential builder is used.
    */
   static withTarget(target: string, service: string, username: string): Entry
+  /**
+   * Create an entry from the specifier returned by `toJSON`.
+   *
+   * The default credential builder is used.
+   */
+  static fromSpecifier(specifier: EntrySpecifier): Entry
+  /**
+   * Create an entry for a URL, e.g. `https://user@registry.example.com`.
+   *
+   * The service is the URL's origin by default, normalized so that every
+   * spelling of it, with or without a default port or trailing slash, gives
+   * the same entry. The user comes from the URL or from `options.user`.
+   */
+  static fromUrl(url: string, options?: UrlOptions | undefined | null): Entry
+  /**
+   * The service of this entry.
+   *
+   * `null` for the handles of an `Ambiguous` error on stores that cannot
+   * tell which service a credential belongs to.
+   */
+  get service(): string | null
+  /** The user of this entry, `null` when the `service` is. */
+  get user(): string | null
+  /** The target of this entry, if it has one. */
+  get target(): string | null
+  /**
+   * The specifier of this entry, to be stored or sent to another thread and
+   * turned back into an entry with `fromSpecifier`.
+   *
+   * `null` when the `service` is.
+   */
+  toJSON(): EntrySpecifier | null
   /**
    * Set the password for this entry.
    *
@@ -98,8 +209,11 @@ export declare class Entry {
    * that matches this entry.  This can only happen
    * on some platforms, and then only if a third-party
    * application wrote the ambiguous credential.
+   *
+   * With `ttlSeconds`, the credential is deleted once that many seconds
+   * have passed, see `getExpiry`. Without it, any expiry is cleared.
    */
-  setPassword(password: string): void
+  setPassword(password: string, options?: SetOptions | undefined | null): void
   /**
    * Set the secret for this entry.
    *
@@ -108,8 +222,30 @@ export declare class Entry {
    * that matches this entry.  This can only happen
    * on some platforms, and then only if a third-party
    * application wrote the ambiguous credential.
+   *
+   * Takes the same options as `setPassword`.
    */
-  setSecret(secret: Uint8Array): void
+  setSecret(secret: Uint8Array, options?: SetOptions | undefined | null): void
+  /**
+   * Set the password for this entry if its current password is `expected`,
+   * or if there is no credential when `expected` is `null`.
+   *
+   * Returns whether the password was written. The check and the write are
+   * serialized with every other conditional write to the same credential,
+   * in this process and, through an advisory lock file, in other processes
+   * using this module. Plain writes are not serialized.
+   *
+   * Takes the same options as `setPassword`: like any write, it clears the
+   * expiry of the credential unless `ttlSeconds` is given.
+   */
+  setPasswordIf(expected: string | undefined | null, next: string, options?: SetOptions | undefined | null): boolean
+  /**
+   * Set the secret for this entry unless there is a credential already.
+   *
+   * Returns whether the secret was written, and takes the same options as
+   * `setPasswordIf`.
+   */
+  setSecretIfAbsent(secret: Uint8Array, options?: SetOptions | undefined | null): boolean
   /**
    * Retrieve the password saved for this entry.
    *
@@ -134,6 +270,79 @@ export declare class Entry {
    * application wrote the ambiguous credential.
    */
   getSecret(): Array<number> | null
+  /**
+   * Check whether there is a credential for this entry.
+   *
+   * Only the store's metadata is consulted: the secret is not read and a
+   * locked credential is reported as `locked` rather than unlocked.
+   */
+  exists(): CredentialState
+  /**
+   * The number of seconds until this entry's credential expires, or `null`
+   * if it never does.
+   *
+   * keyutils keys expire in the kernel, which only reports the remaining
+   * time in its largest whole unit, from seconds up to weeks, so the result
+   * is rounded down to that unit. On other stores that support expiry, an
+   * expired credential is deleted the next time it is read.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+   */
+  getExpiry(): number | null
+  /**
+   * Retrieve the attributes the store keeps with this entry's credential.
+   *
+   * Secret Service items include their `label`, and keyutils keys, which
+   * have no attributes, report their `description`.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+   */
+  getAttributes(): Record<string, string> | null
+  /**
+   * Update the attributes the store keeps with this entry's credential.
+   *
+   * Attributes that identify the credential, such as the service, user and
+   * target, cannot be updated and are rejected with an `Invalid` error, as
+   * is any attribute of a keyutils key. On the Secret Service, `label`
+   * updates the item's label.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+   */
+  updateAttributes(attributes: Record<string, string>): void
+  /**
+   * Retrieve the permissions of this entry's keyutils key, for each class
+   * of processes.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one, and a
+   * `NotSupported` error on other stores.
+   */
+  getPermissions(): KeyPermissions | null
+  /**
+   * Set the permissions of this entry's keyutils key. Classes left out of
+   * `permissions` keep theirs.
+   *
+   * Needs the `setattr` permission, so a key that loses it keeps its new
+   * permissions until it is deleted.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one, and a
+   * `NotSupported` error on other stores.
+   */
+  setPermissions(permissions: KeyPermissions): void
+  /**
+   * Link this entry's keyutils key to the session keyring, which child
+   * processes inherit, so entries for the same service and user find it
+   * there, and return its serial number.
+   *
+   * A process without a session keyring joins a new one first, which only
+   * children spawned afterwards inherit. Children possess the key just like
+   * this process, so `options.permissions` restrict both: e.g. possessors
+   * with only `view`, `read` and `search` can read and delete the key, but
+   * not update it.
+   *
+   * Returns a [NoEntry](Error::NoEntry) error if there isn't one, and a
+   * `NotSupported` error on other stores.
+   */
+  shareWithChildren(options?: ShareOptions | undefined | null): number
   /**
    * Delete the underlying credential for this entry.
    *
@@ -150,17 +359,513 @@ export declare class Entry {
    * affects the underlying credential store.
    */
   deleteCredential(): boolean
+  /**
+   * Delete every credential matching this entry but the one to `keep`.
+   *
+   * Returns how many credentials were deleted, which is zero unless the
+   * entry is ambiguous. `keep` is `'newest'` or `'oldest'` by creation
+   * time, or an index into the `credentials` of the `Ambiguous` error.
+   */
+  resolveAmbiguity(options: ResolveOptions): number
   /** Alias for `deleteCredential` */
   deletePassword(): boolean
 }
 
+/** Inspect and control the mock store, see `getMockStore`. */
+export declare class MockStore {
+  /** List the credentials currently held by the store. */
+  credentials(): Array<MockCredential>
+  /** Take a copy of the store contents that can be passed to `restore`. */
+  snapshot(): Array<MockCredential>
+  /** Replace the store contents with a snapshot. */
+  restore(snapshot: Array<MockCredential>): void
+  /** Remove all credentials and pending failures and delays. */
+  clear(): void
+  /**
+   * Reject secrets longer than `length` bytes with a `TooLong` error, as
+   * platform stores with a size limit do, or accept any length if `null`.
+   */
+  setMaxSecretLength(length?: number | undefined | null): void
+  /**
+   * Make the next matching `operation` fail with an error of the given `code`.
+   *
+   * `Timeout` and `Aborted` are not store errors and cannot be injected.
+   */
+  failNext(operation: MockOperation, code: ErrorCode, options?: MockFailureOptions | undefined | null): void
+  /** Make the next matching `operation` wait `delayMs` milliseconds before it runs. */
+  delayNext(operation: MockOperation, delayMs: number, options?: MockFailureOptions | undefined | null): void
+}
+
+/** A credential found by `listAccounts`, with a handle to read its secret on demand. */
+export interface Account {
+  service: string
+  account: string
+  target?: string
+  label?: string
+  attributes: Record<string, string>
+  created?: number
+  modified?: number
+  locked: boolean
+  /** Why the metadata could not be read. */
+  error?: CredentialError
+  entry: Entry
+}
+
+/** A credential found by `listAccountsAsync`, with a handle to read its secret on demand. */
+export interface AsyncAccount {
+  service: string
+  account: string
+  target?: string
+  label?: string
+  attributes: Record<string, string>
+  created?: number
+  modified?: number
+  locked: boolean
+  /** Why the metadata could not be read. */
+  error?: CredentialError
+  entry: AsyncEntry
+}
+
+/** Options of `findCredentialsAsync`. */
+export interface AsyncFindOptions {
+  /** See `FindOptions.includeSecrets`. */
+  includeSecrets?: boolean
+  /** See `FindOptions.locked`. */
+  locked?: LockedItems
+  /** See `FindOptions.promptTimeoutSeconds`. */
+  promptTimeoutSeconds?: number
+  /** See `AsyncOptions.timeoutMs`. */
+  timeoutMs?: number
+}
+
+/** Options accepted by every `AsyncEntry` method and async function. */
+export interface AsyncOptions {
+  /**
+   * Reject with a `Timeout` error if the operation has not finished after
+   * this many milliseconds.
+   */
+  timeoutMs?: number
+}
+
+/** Options of `AsyncEntry.setPassword`, `AsyncEntry.setSecret` and the conditional writes. */
+export interface AsyncSetOptions {
+  /** See `AsyncOptions.timeoutMs`. */
+  timeoutMs?: number
+  /** Delete the credential after this many seconds, see `Entry.setPassword`. */
+  ttlSeconds?: number
+}
+
+/** Credential store backends that can be selected with `configureStore`. */
+export type Backend = 'secret-service'|
+'keyutils'|
+'keychain'|
+'windows'|
+'mock';
+
+/** The outcome of writing or deleting one credential of a batch. */
+export interface BatchResult {
+  service: string
+  user: string
+  target?: string
+  /** Why the credential could not be written or deleted. */
+  error?: CredentialError
+}
+
+/** A Secret Service collection, which `Entry.withTarget` refers to by label. */
+export interface Collection {
+  label: string
+  /** The D-Bus object path of the collection. */
+  path: string
+  locked: boolean
+  /**
+   * Whether the `default` alias points to it, i.e. entries without a
+   * target are written to it.
+   */
+  isDefault: boolean
+}
+
+/**
+ * Select the credential store used by entries created after this call.
+ *
+ * Without a `backend`, the platform default store is used. If the requested
+ * backend cannot be created, an error is thrown and the current store is kept.
+ *
+ * Entries created before this call keep using the previous store.
+ */
+export declare function configureStore(config?: StoreConfig | undefined | null): void
+
+/**
+ * Create a Secret Service collection, which may prompt the user for its
+ * password, and return it. `alias`, e.g. `'default'`, is pointed at it.
+ *
+ * Throws an `Invalid` error if a collection with this label exists already.
+ */
+export declare function createCollection(label: string, alias?: string | undefined | null): Collection
+
+/** Create a Secret Service collection, see `createCollection`. */
+export declare function createCollectionAsync(label: string, alias?: string | undefined | null, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Collection>
+
 export interface Credential {
+  /** The service the credential belongs to. */
+  service: string
   account: string
+  /** The target the credential was written to, e.g. the Secret Service collection. */
+  target?: string
+  /** The label shown by credential managers, or the key description on keyutils. */
+  label?: string
+  /** Every attribute the store keeps with the credential. */
+  attributes: Record<string, string>
+  /** When the credential was created, in milliseconds since the Unix epoch. */
+  created?: number
+  /** When the credential was last modified, in milliseconds since the Unix epoch. */
+  modified?: number
+  /** Whether the credential was locked when it was found. */
+  locked: boolean
+  /** The raw secret, absent if it could not be read. */
+  secret?: Uint8Array
+  /** The secret decoded as UTF-8, absent if it is not valid UTF-8. */
+  password?: string
+  /** Why `secret` or `password` is missing. */
+  error?: CredentialError
+}
+
+export interface CredentialError {
+  code: ErrorCode
+  message: string
+}
+
+/** A credential to read or delete in a batch. */
+export interface CredentialId {
+  service: string
+  user: string
+  target?: string
+}
+
+/** Which credentials to search for. Every given field must match. */
+export interface CredentialQuery {
+  service?: string
+  user?: string
+  /** Ignored by stores that don't record a target. */
+  target?: string
+  /** Attributes the store keeps with the credential, see `Credential.attributes`. */
+  attributes?: Record<string, string>
+}
+
+/** Whether the store has a credential for an entry, see `Entry.exists`. */
+export type CredentialState = 'absent'|
+'present'|
+'locked'|
+'ambiguous';
+
+/**
+ * Delete the Secret Service collection with this label, and every item in it.
+ *
+ * The `default` collection cannot be deleted. Returns a `NoEntry` error if
+ * there is no such collection.
+ */
+export declare function deleteCollection(label: string): boolean
+
+/** Delete a Secret Service collection, see `deleteCollection`. */
+export declare function deleteCollectionAsync(label: string, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<boolean>
+
+/**
+ * Delete several credentials at once.
+ *
+ * Each credential gets its own result, with an `error` if it could not be
+ * deleted, e.g. a `NoEntry` error if there was none.
+ */
+export declare function deleteCredentials(ids: Array<CredentialId>): Array<BatchResult>
+
+/** Delete several credentials at once, see `deleteCredentials`. */
+export declare function deleteCredentialsAsync(ids: Array<CredentialId>, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Array<BatchResult>>
+
+/** What identifies an entry, as returned by `toJSON` and taken by `fromSpecifier`. */
+export interface EntrySpecifier {
+  service: string
+  user: string
+  target?: string
+}
+
+/** Stable `code` carried by every error thrown or rejected by this module. */
+export type ErrorCode = 'NoEntry'|
+'Ambiguous'|
+'NoStorageAccess'|
+'PlatformFailure'|
+'BadEncoding'|
+'TooLong'|
+'Invalid'|
+'NotSupported'|
+'Timeout'|
+'Aborted';
+
+/**
+ * find credentials by service name
+ *
+ * The active credential store is searched, see `configureStore`. A credential
+ * whose secret cannot be read or decoded is returned with an `error` instead
+ * of failing the whole search.
+ */
+export declare function findCredentials(service: string, target?: string | undefined | null, options?: FindOptions | undefined | null): Array<Credential>
+
+/**
+ * find credentials by service name
+ *
+ * The active credential store is searched, see `configureStore`. A credential
+ * whose secret cannot be read or decoded is returned with an `error` instead
+ * of failing the whole search.
+ */
+export declare function findCredentialsAsync(service: string, target?: string | undefined | null, signal?: AbortSignal | undefined | null, options?: AsyncFindOptions | undefined | null): Promise<Array<Credential>>
+
+export interface FindOptions {
+  /**
+   * Read the secret of every credential. Defaults to `true`.
+   *
+   * Reading secrets may prompt the user to unlock the store. Without them,
+   * only identities and metadata are returned.
+   */
+  includeSecrets?: boolean
+  /** What to do with locked credentials. Defaults to `'report'`. */
+  locked?: LockedItems
+  /** How long an unlock prompt waits for the user, in seconds. Defaults to no limit. */
+  promptTimeoutSeconds?: number
+}
+
+/**
+ * The Secret Service collection an alias points to, `'default'` unless
+ * given, or nothing if the alias is not set.
+ */
+export declare function getCollectionByAlias(alias?: string | undefined | null): Collection | null
+
+/** The Secret Service collection an alias points to, see `getCollectionByAlias`. */
+export declare function getCollectionByAliasAsync(alias?: string | undefined | null, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Collection | undefined>
+
+/**
+ * Get a handle to the active mock store.
+ *
+ * Throws a `NotSupported` error unless `configureStore({ backend: 'mock' })` was called.
+ */
+export declare function getMockStore(): MockStore
+
+/**
+ * Read the passwords of several credentials at once.
+ *
+ * Each credential gets its own result, with an `error` instead of a
+ * `password` if it could not be read or decoded. On the Secret Service,
+ * locked credentials are unlocked with a single prompt and the secrets are
+ * read with one call. Expired credentials are deleted and reported with a
+ * `NoEntry` error, as by `Entry.getPassword`.
+ */
+export declare function getPasswords(ids: Array<CredentialId>): Array<PasswordResult>
+
+/** Read the passwords of several credentials at once, see `getPasswords`. */
+export declare function getPasswordsAsync(ids: Array<CredentialId>, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Array<PasswordResult>>
+
+/**
+ * Describe the credential store used by new entries.
+ *
+ * The platform default store is set up first if no store is configured yet.
+ */
+export declare function getStoreInfo(): StoreInfo
+
+/** Which of several matching credentials `resolveAmbiguity` keeps. */
+export type KeepCredential = 'newest'|
+'oldest';
+
+/** What a class of processes may do with a keyutils key, see `KeyPermissions`. */
+export type KeyPermission = 'view'|
+'read'|
+'write'|
+'search'|
+'link'|
+'setattr';
+
+/**
+ * The permissions of a keyutils key, for each class of processes.
+ *
+ * A process gets the permissions of every class it belongs to. Classes left
+ * out keep their permissions when they are set.
+ */
+export interface KeyPermissions {
+  /** Processes that possess the key, i.e. reach it from their own keyrings. */
+  possessor?: Array<KeyPermission>
+  /** Processes of the user that owns the key. */
+  user?: Array<KeyPermission>
+  /** Processes of the group that owns the key. */
+  group?: Array<KeyPermission>
+  /** Every other process. */
+  other?: Array<KeyPermission>
+}
+
+/**
+ * List the accounts of a service without reading their secrets.
+ *
+ * Each account comes with an entry to read its secret on demand.
+ */
+export declare function listAccounts(service: string, target?: string | undefined | null): Array<Account>
+
+/**
+ * List the accounts of a service without reading their secrets.
+ *
+ * Each account comes with an entry to read its secret on demand.
+ */
+export declare function listAccountsAsync(service: string, target?: string | undefined | null, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Array<AsyncAccount>>
+
+/**
+ * List the Secret Service collections, without unlocking them.
+ *
+ * Throws a `NotSupported` error on platforms without the Secret Service.
+ */
+export declare function listCollections(): Array<Collection>
+
+/** List the Secret Service collections, see `listCollections`. */
+export declare function listCollectionsAsync(signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Array<Collection>>
+
+/** What `findCredentials` does with credentials that are locked. */
+export type LockedItems = 'skip'|
+'report'|
+'unlock';
+
+/** A credential held by the mock store. */
+export interface MockCredential {
+  service: string
+  user: string
+  target?: string
+  secret: Uint8Array
+  attributes?: Record<string, string>
+  /** When the credential was created, in milliseconds since the Unix epoch. */
+  created?: number
+  /** When the secret was last written, in milliseconds since the Unix epoch. */
+  modified?: number
+  /** A locked credential can only be used after it is unlocked. */
+  locked?: boolean
+}
+
+/** Restricts an injected failure or delay to matching credentials. */
+export interface MockFailureOptions {
+  service?: string
+  user?: string
+  /** How many matching calls are affected, at least 1. Defaults to 1. */
+  times?: number
+}
+
+/** Store operations that can be made to fail or wait with `MockStore.failNext` and `MockStore.delayNext`. */
+export type MockOperation = 'get'|
+'set'|
+'delete'|
+'search'|
+'unlock';
+
+/** A password to write in a batch. */
+export interface PasswordItem {
+  service: string
+  user: string
+  target?: string
   password: string
 }
 
-/** find credentials by service name */
-export declare function findCredentials(service: string, target?: string | undefined | null): Array<Credential>
+/** The outcome of reading one password of a batch. */
+export interface PasswordResult {
+  service: string
+  user: string
+  target?: string
+  /** The password, unless it could not be read or decoded. */
+  password?: string
+  /** Why the password could not be read or decoded. */
+  error?: CredentialError
+}
 
-/** find credentials by service name */
-export declare function findCredentialsAsync(service: string, target?: string | undefined | null, signal?: AbortSignal | undefined | null): Promise<Array<Credential>>
+/** How long credentials written to a store survive. */
+export type Persistence = 'entry-only'|
+'process-only'|
+'until-logout'|
+'until-reboot'|
+'until-delete'|
+'unspecified';
+
+export interface ResolveOptions {
+  /**
+   * The credential to keep, by creation time or by its index in the
+   * `credentials` of the `Ambiguous` error.
+   */
+  keep: 'newest' | 'oldest' | number
+}
+
+/** Search the active store and return an entry for each matching credential. */
+export declare function searchCredentials(query: CredentialQuery): Array<Entry>
+
+/** Search the active store and resolve to an entry for each matching credential. */
+export declare function searchCredentialsAsync(query: CredentialQuery, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Array<AsyncEntry>>
+
+/**
+ * Restore the lenient behavior of earlier versions.
+ *
+ * When enabled, `getPassword` and `getSecret` resolve to `null`/`undefined`
+ * and `deleteCredential` to `false` on any error instead of throwing.
+ */
+export declare function setLenientErrors(enabled: boolean): void
+
+/** Options of `Entry.setPassword`, `Entry.setSecret` and the conditional writes. */
+export interface SetOptions {
+  /**
+   * Delete the credential after this many seconds. Not supported by the
+   * macOS Keychain and the Windows Credential Manager.
+   */
+  ttlSeconds?: number
+}
+
+/**
+ * Write the passwords of several credentials at once.
+ *
+ * Each credential gets its own result, with an `error` if it could not be
+ * written. As with `Entry.setPassword` without `ttlSeconds`, the credentials
+ * no longer expire.
+ */
+export declare function setPasswords(items: Array<PasswordItem>): Array<BatchResult>
+
+/** Write the passwords of several credentials at once, see `setPasswords`. */
+export declare function setPasswordsAsync(items: Array<PasswordItem>, signal?: AbortSignal | undefined | null, options?: AsyncOptions | undefined | null): Promise<Array<BatchResult>>
+
+/** Options of `Entry.shareWithChildren`. */
+export interface ShareOptions {
+  /**
+   * The permissions to give the key once it is shared, e.g. only `view`,
+   * `read` and `search` for possessors so children cannot update it.
+   */
+  permissions?: KeyPermissions
+}
+
+export interface StoreConfig {
+  /** The backend to use. Defaults to the platform default store. */
+  backend?: Backend
+  /** Configuration passed to the backend when it is created. */
+  options?: Record<string, string>
+}
+
+export interface StoreInfo {
+  /** The active backend. */
+  backend: Backend
+  /** The vendor string reported by the store, naming its implementation. */
+  vendor: string
+  /** The id reported by the store, including its version. */
+  id: string
+  /** How long credentials written to the store survive. */
+  persistence: Persistence
+  /** Why the platform default store was not used, if a fallback happened. */
+  fallbackReason?: string
+  /** The serial number of the keyutils keyring credentials are written to. */
+  keyringSerial?: number
+}
+
+/** How much of a URL goes into the service of `Entry.fromUrl`. */
+export type UrlGranularity = 'origin'|
+'host'|
+'path';
+
+export interface UrlOptions {
+  /** The user, for URLs without one. A user in the URL takes precedence. */
+  user?: string
+  /** Defaults to `'origin'`. */
+  granularity?: UrlGranularity
+  /** The target to create the entry with, as in `Entry.withTarget`. */
+  target?: string
+}
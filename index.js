@@ -510,5 +510,28 @@ if (!nativeBinding) {
 module.exports = nativeBinding
 module.exports.AsyncEntry = nativeBinding.AsyncEntry
 module.exports.Entry = nativeBinding.Entry
+module.exports.MockStore = nativeBinding.MockStore
+module.exports.configureStore = nativeBinding.configureStore
+module.exports.createCollection = nativeBinding.createCollection
+module.exports.createCollectionAsync = nativeBinding.createCollectionAsync
+module.exports.deleteCollection = nativeBinding.deleteCollection
+module.exports.deleteCollectionAsync = nativeBinding.deleteCollectionAsync
+module.exports.deleteCredentials = nativeBinding.deleteCredentials
+module.exports.deleteCredentialsAsync = nativeBinding.deleteCredentialsAsync
 module.exports.findCredentials = nativeBinding.findCredentials
 module.exports.findCredentialsAsync = nativeBinding.findCredentialsAsync
+module.exports.getCollectionByAlias = nativeBinding.getCollectionByAlias
+module.exports.getCollectionByAliasAsync = nativeBinding.getCollectionByAliasAsync
+module.exports.getMockStore = nativeBinding.getMockStore
+module.exports.getPasswords = nativeBinding.getPasswords
+module.exports.getPasswordsAsync = nativeBinding.getPasswordsAsync
+module.exports.getStoreInfo = nativeBinding.getStoreInfo
+module.exports.listAccounts = nativeBinding.listAccounts
+module.exports.listAccountsAsync = nativeBinding.listAccountsAsync
+module.exports.listCollections = nativeBinding.listCollections
+module.exports.listCollectionsAsync = nativeBinding.listCollectionsAsync
+module.exports.searchCredentials = nativeBinding.searchCredentials
+module.exports.searchCredentialsAsync = nativeBinding.searchCredentialsAsync
+module.exports.setLenientErrors = nativeBinding.setLenientErrors
+module.exports.setPasswords = nativeBinding.setPasswords
+module.exports.setPasswordsAsync = nativeBinding.setPasswordsAsync
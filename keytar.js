@@ -1,8 +1,18 @@
+const { NoEntryError } = require('./errors.js')
 const { AsyncEntry, findCredentialsAsync } = require('./index.js')
 
+function missingAs(value) {
+  return (error) => {
+    if (error instanceof NoEntryError) {
+      return value
+    }
+    throw error
+  }
+}
+
 module.exports.getPassword = function getPassword(service, account) {
   const entry = new AsyncEntry(service, account)
-  return entry.getPassword()
+  return entry.getPassword().catch(missingAs(null))
 }
 
 module.exports.setPassword = function setPassword(service, account, password) {
@@ -12,15 +22,22 @@ module.exports.setPassword = function setPassword(service, account, password) {
 
 module.exports.deletePassword = function deletePassword(service, account) {
   const entry = new AsyncEntry(service, account)
-  return entry.deletePassword()
+  return entry.deletePassword().catch(missingAs(false))
 }
 
-module.exports.findPassword = async function findPassword(service) {
+async function findPasswords(service) {
   const credentials = await findCredentialsAsync(service)
+  return credentials
+    .filter((credential) => credential.password !== undefined)
+    .map(({ account, password }) => ({ account, password }))
+}
+
+module.exports.findPassword = async function findPassword(service) {
+  const credentials = await findPasswords(service)
   if (!credentials.length) {
     return null
   }
   return credentials[0].password
 }
 
-module.exports.findCredentials = findCredentialsAsync
+module.exports.findCredentials = findPasswords
@@ -0,0 +1,73 @@
+use keyring_core::{Entry, Error, Result};
+use napi::Either;
+use napi_derive::napi;
+
+use crate::chunked;
+use crate::search::describe;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Which of several matching credentials `resolveAmbiguity` keeps.
+pub enum KeepCredential {
+  /// The most recently created credential.
+  Newest,
+  /// The least recently created credential.
+  Oldest,
+}
+
+#[napi(object)]
+pub struct ResolveOptions {
+  /// The credential to keep, by creation time or by its index in the
+  /// `credentials` of the `Ambiguous` error.
+  #[napi(ts_type = "'newest' | 'oldest' | number")]
+  pub keep: Either<KeepCredential, u32>,
+}
+
+/// Delete every credential matching `entry` but the one to `keep`, returning
+/// how many were deleted. Nothing is deleted unless the entry is ambiguous.
+pub(crate) fn resolve(entry: &Entry, keep: &Either<KeepCredential, u32>) -> Result<u32> {
+  let matches = match entry.get_credential() {
+    Ok(_) => return Ok(0),
+    Err(Error::Ambiguous(matches)) => matches,
+    Err(err) => return Err(err),
+  };
+  let keep = match keep {
+    Either::A(which) => {
+      let created = matches
+        .iter()
+        .map(|entry| {
+          describe(entry)?.created.ok_or_else(|| {
+            Error::NotSupportedByStore(
+              "The store does not record when credentials were created".to_owned(),
+            )
+          })
+        })
+        .collect::<Result<Vec<_>>>()?;
+      let order = created.iter().enumerate();
+      let kept = match which {
+        KeepCredential::Newest => order.max_by_key(|(_, created)| **created),
+        KeepCredential::Oldest => order.min_by_key(|(_, created)| **created),
+      };
+      kept.map(|(index, _)| index).unwrap_or_default()
+    }
+    Either::B(index) => {
+      let index = *index as usize;
+      if index >= matches.len() {
+        return Err(Error::Invalid(
+          "keep".to_owned(),
+          format!("there are only {} matching credentials", matches.len()),
+        ));
+      }
+      index
+    }
+  };
+  // Delete from the last match, so earlier handles still address the same credential.
+  let mut deleted = 0;
+  for (index, entry) in matches.iter().enumerate().rev() {
+    if index != keep {
+      chunked::delete_credential(entry)?;
+      deleted += 1;
+    }
+  }
+  Ok(deleted)
+}
@@ -1,54 +1,53 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-#[cfg(target_os = "linux")]
-use crate::linux_credential_builder::LinuxCredentialBuilder;
-
-#[napi]
-pub struct AsyncEntry {
-  inner: Arc<keyring_core::Entry>,
+use crate::ambiguity::{self, KeepCredential, ResolveOptions};
+use crate::attributes;
+use crate::chunked;
+use crate::conditional;
+use crate::error::{ErrorCode, deleted, optional, to_js_error, to_napi_error};
+use crate::exists::{self, CredentialState};
+use crate::expiry;
+use crate::permissions::{self, KeyPermissions, ShareOptions};
+use crate::specifier::{EntrySpecifier, build_entry, specifier, target_of};
+use crate::store::ensure_default_store;
+use crate::url::{self, UrlOptions};
+use crate::worker::WorkerTask;
+
+#[napi(object)]
+/// Options accepted by every `AsyncEntry` method and async function.
+pub struct AsyncOptions {
+  /// Reject with a `Timeout` error if the operation has not finished after
+  /// this many milliseconds.
+  pub timeout_ms: Option<u32>,
 }
 
-#[cfg(target_os = "linux")]
-fn setup_linux_store() -> anyhow::Result<()> {
-  let builder = LinuxCredentialBuilder::new()?;
-  keyring_core::set_default_store(builder.get_store());
-  Ok(())
-}
-
-#[cfg(target_os = "macos")]
-fn setup_macos_store() -> anyhow::Result<()> {
-  use std::collections::HashMap;
-
-  use apple_native_keyring_store::keychain::Store;
-
-  let store = Store::new_with_configuration(&HashMap::new())?;
-  keyring_core::set_default_store(store);
-  Ok(())
+#[napi(object)]
+/// Options of `AsyncEntry.setPassword`, `AsyncEntry.setSecret` and the conditional writes.
+pub struct AsyncSetOptions {
+  /// See `AsyncOptions.timeoutMs`.
+  pub timeout_ms: Option<u32>,
+  /// Delete the credential after this many seconds, see `Entry.setPassword`.
+  pub ttl_seconds: Option<u32>,
 }
 
-#[cfg(target_os = "windows")]
-fn setup_windows_store() -> anyhow::Result<()> {
-  use std::collections::HashMap;
-
-  use windows_native_keyring_store::Store;
-
-  let store = Store::new_with_configuration(&HashMap::new())?;
-  keyring_core::set_default_store(store);
-  Ok(())
+#[napi]
+pub struct AsyncEntry {
+  inner: Arc<keyring_core::Entry>,
+  /// The target the entry was created with, or the one its store records.
+  target: Option<String>,
 }
 
-#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
-fn setup_bsd_store() -> anyhow::Result<()> {
-  use std::collections::HashMap;
-
-  use dbus_secret_service_keyring_store::Store;
-
-  let store = Store::new_with_configuration(&HashMap::new())?;
-  keyring_core::set_default_store(store);
-  Ok(())
+impl From<keyring_core::Entry> for AsyncEntry {
+  fn from(inner: keyring_core::Entry) -> Self {
+    Self {
+      target: target_of(&inner),
+      inner: Arc::new(inner),
+    }
+  }
 }
 
 #[napi]
@@ -57,18 +56,12 @@ impl AsyncEntry {
   /// Create an entry for the given service and username.
   ///
   /// The default credential builder is used.
-  pub fn new(service: String, username: String) -> Result<Self> {
-    #[cfg(target_os = "linux")]
-    setup_linux_store()?;
-    #[cfg(target_os = "macos")]
-    setup_macos_store()?;
-    #[cfg(target_os = "windows")]
-    setup_windows_store()?;
-    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
-    setup_bsd_store()?;
+  pub fn new(env: Env, service: String, username: String) -> Result<Self, ErrorCode> {
+    ensure_default_store(&env).map_err(to_napi_error)?;
 
     Ok(Self {
-      inner: Arc::new(keyring_core::Entry::new(&service, &username).map_err(anyhow::Error::from)?),
+      inner: Arc::new(build_entry(&service, &username, None).map_err(to_napi_error)?),
+      target: None,
     })
   }
 
@@ -76,28 +69,17 @@ impl AsyncEntry {
   /// Create an entry for the given target, service, and username.
   ///
   /// The default credential builder is used.
-  pub fn with_target(target: String, service: String, username: String) -> Result<Self> {
-    #[cfg(target_os = "linux")]
-    setup_linux_store()?;
-    #[cfg(target_os = "macos")]
-    setup_macos_store()?;
-    #[cfg(target_os = "windows")]
-    setup_windows_store()?;
-    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
-    setup_bsd_store()?;
+  pub fn with_target(
+    env: Env,
+    target: String,
+    service: String,
+    username: String,
+  ) -> Result<Self, ErrorCode> {
+    ensure_default_store(&env).map_err(to_napi_error)?;
 
     let entry = Self {
-      inner: Arc::new(
-        keyring_core::Entry::new_with_modifiers(&service, &username, &{
-          let mut mods = std::collections::HashMap::new();
-          #[cfg(target_os = "macos")]
-          mods.insert("keychain", target.as_str());
-          #[cfg(not(target_os = "macos"))]
-          mods.insert("target", target.as_str());
-          mods
-        })
-        .map_err(anyhow::Error::from)?,
-      ),
+      inner: Arc::new(build_entry(&service, &username, Some(&target)).map_err(to_napi_error)?),
+      target: Some(target.clone()),
     };
 
     // On Windows, when using the target modifier, the username needs to be preserved
@@ -118,6 +100,55 @@ impl AsyncEntry {
     Ok(entry)
   }
 
+  #[napi(factory)]
+  /// Create an entry from the specifier returned by `toJSON`, see `Entry.fromSpecifier`.
+  pub fn from_specifier(env: Env, specifier: EntrySpecifier) -> Result<Self, ErrorCode> {
+    match specifier.target {
+      Some(target) => Self::with_target(env, target, specifier.service, specifier.user),
+      None => Self::new(env, specifier.service, specifier.user),
+    }
+  }
+
+  #[napi(factory)]
+  /// Create an entry for a URL, see `Entry.fromUrl`.
+  pub fn from_url(env: Env, url: String, options: Option<UrlOptions>) -> Result<Self, ErrorCode> {
+    let (service, user) = url::specifiers(&url, options.as_ref()).map_err(to_napi_error)?;
+    match options.and_then(|options| options.target) {
+      Some(target) => Self::with_target(env, target, service, user),
+      None => Self::new(env, service, user),
+    }
+  }
+
+  #[napi(getter)]
+  /// The service of this entry.
+  ///
+  /// `null` for the handles of an `Ambiguous` error on stores that cannot
+  /// tell which service a credential belongs to.
+  pub fn service(&self) -> Option<String> {
+    self.inner.get_specifiers().map(|(service, _)| service)
+  }
+
+  #[napi(getter)]
+  /// The user of this entry, `null` when the `service` is.
+  pub fn user(&self) -> Option<String> {
+    self.inner.get_specifiers().map(|(_, user)| user)
+  }
+
+  #[napi(getter)]
+  /// The target of this entry, if it has one.
+  pub fn target(&self) -> Option<String> {
+    self.target.clone()
+  }
+
+  #[napi(js_name = "toJSON")]
+  /// The specifier of this entry, to be stored or sent to another thread and
+  /// turned back into an entry with `fromSpecifier`.
+  ///
+  /// `null` when the `service` is.
+  pub fn to_json(&self) -> Option<EntrySpecifier> {
+    specifier(&self.inner, self.target.as_deref())
+  }
+
   #[napi(ts_return_type = "Promise<void>")]
   /// Set the password for this entry.
   ///
@@ -126,18 +157,25 @@ impl AsyncEntry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
+  ///
+  /// With `ttlSeconds`, the credential expires, see `Entry.setPassword`.
   pub fn set_password(
     &self,
     password: String,
     signal: Option<AbortSignal>,
-  ) -> AsyncTask<EntryTask> {
-    AsyncTask::with_optional_signal(
+    options: Option<AsyncSetOptions>,
+  ) -> WorkerTask<EntryTask> {
+    WorkerTask::with_optional_signal(
       EntryTask {
         inner: self.inner.clone(),
-        kind: TaskKind::SetPassword(password),
+        kind: TaskKind::SetPassword(
+          password,
+          options.as_ref().and_then(|options| options.ttl_seconds),
+        ),
       },
       signal,
     )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
   }
 
   #[napi(ts_return_type = "Promise<void>")]
@@ -148,14 +186,71 @@ impl AsyncEntry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  pub fn set_secret(&self, secret: &[u8], signal: Option<AbortSignal>) -> AsyncTask<EntryTask> {
-    AsyncTask::with_optional_signal(
+  ///
+  /// Takes the same options as `setPassword`.
+  pub fn set_secret(
+    &self,
+    secret: &[u8],
+    signal: Option<AbortSignal>,
+    options: Option<AsyncSetOptions>,
+  ) -> WorkerTask<EntryTask> {
+    WorkerTask::with_optional_signal(
       EntryTask {
         inner: self.inner.clone(),
-        kind: TaskKind::SetSecret(secret.to_vec()),
+        kind: TaskKind::SetSecret(
+          secret.to_vec(),
+          options.as_ref().and_then(|options| options.ttl_seconds),
+        ),
       },
       signal,
     )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<boolean>")]
+  /// Set the password for this entry if its current password is `expected`,
+  /// or if there is no credential when `expected` is `null`.
+  ///
+  /// Resolves to whether the password was written, see `Entry.setPasswordIf`.
+  /// Takes the same options as `setPassword`.
+  pub fn set_password_if(
+    &self,
+    expected: Option<String>,
+    next: String,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncSetOptions>,
+  ) -> WorkerTask<ConditionalTask> {
+    WorkerTask::with_optional_signal(
+      ConditionalTask {
+        inner: self.inner.clone(),
+        kind: ConditionKind::Password(expected, next),
+        ttl_seconds: options.as_ref().and_then(|options| options.ttl_seconds),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<boolean>")]
+  /// Set the secret for this entry unless there is a credential already.
+  ///
+  /// Resolves to whether the secret was written, see `Entry.setPasswordIf`.
+  /// Takes the same options as `setPassword`.
+  pub fn set_secret_if_absent(
+    &self,
+    secret: &[u8],
+    signal: Option<AbortSignal>,
+    options: Option<AsyncSetOptions>,
+  ) -> WorkerTask<ConditionalTask> {
+    WorkerTask::with_optional_signal(
+      ConditionalTask {
+        inner: self.inner.clone(),
+        kind: ConditionKind::Secret(None, secret.to_vec()),
+        ttl_seconds: options.as_ref().and_then(|options| options.ttl_seconds),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
   }
 
   #[napi(ts_return_type = "Promise<string | undefined>")]
@@ -168,13 +263,18 @@ impl AsyncEntry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  pub fn get_password(&self, signal: Option<AbortSignal>) -> AsyncTask<PasswordTask> {
-    AsyncTask::with_optional_signal(
+  pub fn get_password(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<PasswordTask> {
+    WorkerTask::with_optional_signal(
       PasswordTask {
         inner: self.inner.clone(),
       },
       signal,
     )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
   }
 
   #[napi(ts_return_type = "Promise<Uint8Array | undefined>")]
@@ -187,13 +287,149 @@ impl AsyncEntry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  pub fn get_secret(&self, signal: Option<AbortSignal>) -> AsyncTask<SecretTask> {
-    AsyncTask::with_optional_signal(
+  pub fn get_secret(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<SecretTask> {
+    WorkerTask::with_optional_signal(
       SecretTask {
         inner: self.inner.clone(),
       },
       signal,
     )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<CredentialState>")]
+  /// Check whether there is a credential for this entry, see `Entry.exists`.
+  pub fn exists(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<ExistsTask> {
+    WorkerTask::with_optional_signal(
+      ExistsTask {
+        inner: self.inner.clone(),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<number | undefined>")]
+  /// The number of seconds until this entry's credential expires, see `Entry.getExpiry`.
+  pub fn get_expiry(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<ExpiryTask> {
+    WorkerTask::with_optional_signal(
+      ExpiryTask {
+        inner: self.inner.clone(),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<Record<string, string> | undefined>")]
+  /// Retrieve the attributes the store keeps with this entry's credential.
+  ///
+  /// Secret Service items include their `label`, and keyutils keys, which
+  /// have no attributes, report their `description`.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  pub fn get_attributes(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<AttributesTask> {
+    WorkerTask::with_optional_signal(
+      AttributesTask {
+        inner: self.inner.clone(),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Update the attributes the store keeps with this entry's credential.
+  ///
+  /// Attributes that identify the credential, such as the service, user and
+  /// target, cannot be updated and are rejected with an `Invalid` error, as
+  /// is any attribute of a keyutils key. On the Secret Service, `label`
+  /// updates the item's label.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  pub fn update_attributes(
+    &self,
+    attributes: HashMap<String, String>,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<EntryTask> {
+    WorkerTask::with_optional_signal(
+      EntryTask {
+        inner: self.inner.clone(),
+        kind: TaskKind::UpdateAttributes(attributes),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<KeyPermissions | undefined>")]
+  /// Retrieve the permissions of this entry's keyutils key, see `Entry.getPermissions`.
+  pub fn get_permissions(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<PermissionsTask> {
+    WorkerTask::with_optional_signal(
+      PermissionsTask {
+        inner: self.inner.clone(),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Set the permissions of this entry's keyutils key, see `Entry.setPermissions`.
+  pub fn set_permissions(
+    &self,
+    permissions: KeyPermissions,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<EntryTask> {
+    WorkerTask::with_optional_signal(
+      EntryTask {
+        inner: self.inner.clone(),
+        kind: TaskKind::SetPermissions(permissions),
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<number>")]
+  /// Link this entry's keyutils key to the session keyring, which child
+  /// processes inherit, see `Entry.shareWithChildren`.
+  pub fn share_with_children(
+    &self,
+    share_options: Option<ShareOptions>,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<ShareTask> {
+    WorkerTask::with_optional_signal(
+      ShareTask {
+        inner: self.inner.clone(),
+        options: share_options,
+      },
+      signal,
+    )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
   }
 
   #[napi(ts_return_type = "Promise<boolean>")]
@@ -210,27 +446,57 @@ impl AsyncEntry {
   /// Note: This does _not_ affect the lifetime of the [Entry]
   /// structure, which is controlled by Rust.  It only
   /// affects the underlying credential store.
-  pub fn delete_credential(&self, signal: Option<AbortSignal>) -> AsyncTask<EntryTask> {
-    AsyncTask::with_optional_signal(
+  pub fn delete_credential(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<EntryTask> {
+    WorkerTask::with_optional_signal(
       EntryTask {
         inner: self.inner.clone(),
         kind: TaskKind::DeleteCredential,
       },
       signal,
     )
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+  }
+
+  #[napi(ts_return_type = "Promise<number>")]
+  /// Delete every credential matching this entry but the one to `keep`,
+  /// see `Entry.resolveAmbiguity`.
+  pub fn resolve_ambiguity(
+    &self,
+    options: ResolveOptions,
+    signal: Option<AbortSignal>,
+    async_options: Option<AsyncOptions>,
+  ) -> WorkerTask<ResolveTask> {
+    WorkerTask::with_optional_signal(
+      ResolveTask {
+        inner: self.inner.clone(),
+        keep: options.keep,
+      },
+      signal,
+    )
+    .with_timeout(async_options.and_then(|options| options.timeout_ms))
   }
 
-  #[napi]
+  #[napi(ts_return_type = "Promise<boolean>")]
   /// Alias for `deleteCredential`
-  pub fn delete_password(&self, signal: Option<AbortSignal>) -> AsyncTask<EntryTask> {
-    self.delete_credential(signal)
+  pub fn delete_password(
+    &self,
+    signal: Option<AbortSignal>,
+    options: Option<AsyncOptions>,
+  ) -> WorkerTask<EntryTask> {
+    self.delete_credential(signal, options)
   }
 }
 
 #[allow(clippy::enum_variant_names)]
 enum TaskKind {
-  SetPassword(String),
-  SetSecret(Vec<u8>),
+  SetPassword(String, Option<u32>),
+  SetSecret(Vec<u8>, Option<u32>),
+  UpdateAttributes(HashMap<String, String>),
+  SetPermissions(KeyPermissions),
   DeleteCredential,
 }
 
@@ -246,15 +512,15 @@ pub struct PasswordTask {
 
 #[napi]
 impl Task for PasswordTask {
-  type Output = Option<String>;
+  type Output = keyring_core::Result<String>;
   type JsValue = Option<String>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    Ok(self.inner.get_password().ok())
+    Ok(expiry::get_password(&self.inner))
   }
 
-  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
-    Ok(output)
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    optional(output).map_err(|err| to_js_error(&env, err))
   }
 }
 
@@ -265,42 +531,202 @@ pub struct SecretTask {
 
 #[napi]
 impl Task for SecretTask {
-  type Output = Option<Vec<u8>>;
+  type Output = keyring_core::Result<Vec<u8>>;
   type JsValue = Option<Vec<u8>>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    Ok(self.inner.get_secret().ok())
+    Ok(expiry::get_secret(&self.inner))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    optional(output).map_err(|err| to_js_error(&env, err))
+  }
+}
+
+// Exists task
+pub struct ExistsTask {
+  inner: Arc<keyring_core::Entry>,
+}
+
+#[napi]
+impl Task for ExistsTask {
+  type Output = keyring_core::Result<CredentialState>;
+  type JsValue = CredentialState;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(exists::exists(&self.inner))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
+  }
+}
+
+// Expiry task
+pub struct ExpiryTask {
+  inner: Arc<keyring_core::Entry>,
+}
+
+#[napi]
+impl Task for ExpiryTask {
+  type Output = keyring_core::Result<Option<u32>>;
+  type JsValue = Option<u32>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(expiry::remaining(&self.inner))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    optional(output)
+      .map(Option::flatten)
+      .map_err(|err| to_js_error(&env, err))
+  }
+}
+
+// Resolve task
+pub struct ResolveTask {
+  inner: Arc<keyring_core::Entry>,
+  keep: Either<KeepCredential, u32>,
+}
+
+#[napi]
+impl Task for ResolveTask {
+  type Output = keyring_core::Result<u32>;
+  type JsValue = u32;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(ambiguity::resolve(&self.inner, &self.keep))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
+  }
+}
+
+// Attributes task
+pub struct AttributesTask {
+  inner: Arc<keyring_core::Entry>,
+}
+
+#[napi]
+impl Task for AttributesTask {
+  type Output = keyring_core::Result<HashMap<String, String>>;
+  type JsValue = Option<HashMap<String, String>>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(attributes::get_attributes(&self.inner))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    optional(output).map_err(|err| to_js_error(&env, err))
+  }
+}
+
+// Permissions task
+pub struct PermissionsTask {
+  inner: Arc<keyring_core::Entry>,
+}
+
+#[napi]
+impl Task for PermissionsTask {
+  type Output = keyring_core::Result<KeyPermissions>;
+  type JsValue = Option<KeyPermissions>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(permissions::get(&self.inner))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    optional(output).map_err(|err| to_js_error(&env, err))
+  }
+}
+
+// Share task
+pub struct ShareTask {
+  inner: Arc<keyring_core::Entry>,
+  options: Option<ShareOptions>,
+}
+
+#[napi]
+impl Task for ShareTask {
+  type Output = keyring_core::Result<i32>;
+  type JsValue = i32;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(permissions::share_with_children(
+      &self.inner,
+      self.options.as_ref(),
+    ))
   }
 
-  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
-    Ok(output)
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
+  }
+}
+
+/// The expected and the next value of a conditional write.
+enum ConditionKind {
+  Password(Option<String>, String),
+  Secret(Option<Vec<u8>>, Vec<u8>),
+}
+
+// Conditional write task
+pub struct ConditionalTask {
+  inner: Arc<keyring_core::Entry>,
+  kind: ConditionKind,
+  ttl_seconds: Option<u32>,
+}
+
+#[napi]
+impl Task for ConditionalTask {
+  type Output = keyring_core::Result<bool>;
+  type JsValue = bool;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(match &self.kind {
+      ConditionKind::Password(expected, next) => {
+        conditional::set_password_if(&self.inner, expected.as_deref(), next, self.ttl_seconds)
+      }
+      ConditionKind::Secret(expected, next) => {
+        conditional::set_secret_if(&self.inner, expected.as_deref(), next, self.ttl_seconds)
+      }
+    })
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
   }
 }
 
 // Generic task for operations that don't return values or return booleans
 #[napi]
 impl Task for EntryTask {
-  type Output = Option<bool>;
+  type Output = keyring_core::Result<()>;
   type JsValue = Option<bool>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    match self.kind {
-      TaskKind::DeleteCredential => Ok(Some(self.inner.delete_credential().is_ok())),
-      TaskKind::SetPassword(ref password) => {
-        self
-          .inner
-          .set_password(password)
-          .map_err(anyhow::Error::from)?;
-        Ok(None)
+    Ok(match self.kind {
+      TaskKind::DeleteCredential => chunked::delete_credential(&self.inner),
+      TaskKind::SetPassword(ref password, ttl_seconds) => {
+        expiry::set(&self.inner, ttl_seconds, || {
+          chunked::set_password(&self.inner, password)
+        })
       }
-      TaskKind::SetSecret(ref secret) => {
-        self.inner.set_secret(secret).map_err(anyhow::Error::from)?;
-        Ok(None)
+      TaskKind::SetSecret(ref secret, ttl_seconds) => expiry::set(&self.inner, ttl_seconds, || {
+        chunked::set_secret(&self.inner, secret)
+      }),
+      TaskKind::UpdateAttributes(ref attributes) => {
+        attributes::update_attributes(&self.inner, attributes)
       }
-    }
+      TaskKind::SetPermissions(ref permissions) => permissions::set(&self.inner, permissions),
+    })
   }
 
-  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
-    Ok(output)
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    match self.kind {
+      TaskKind::DeleteCredential => deleted(output).map(Some),
+      _ => output.map(|_| None),
+    }
+    .map_err(|err| to_js_error(&env, err))
   }
 }
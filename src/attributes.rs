@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use keyring_core::{Entry, Result};
+
+use crate::{chunked, expiry};
+
+/// The key under which a Secret Service item's label is read and updated.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+const LABEL: &str = "label";
+
+/// The key under which a keyutils key's description is reported.
+#[cfg(target_os = "linux")]
+const DESCRIPTION: &str = "description";
+
+/// Read the attributes the store keeps with the credential of `entry`.
+///
+/// Secret Service items also report their label, and keyutils keys, which
+/// have no attributes of their own, report their description. An expired
+/// credential is deleted instead, see [expiry::purge]. Attributes this
+/// crate keeps for itself are left out.
+pub(crate) fn get_attributes(entry: &Entry) -> Result<HashMap<String, String>> {
+  let mut attributes = entry.get_attributes()?;
+  expiry::check(entry, &mut attributes)?;
+  chunked::hide(&mut attributes);
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Some(label) = secret_service::get_label(entry) {
+    attributes.insert(LABEL.to_owned(), label?);
+  }
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = entry
+    .as_any()
+    .downcast_ref::<linux_keyutils_keyring_store::Cred>()
+  {
+    attributes.insert(DESCRIPTION.to_owned(), cred.description.clone());
+  }
+  Ok(attributes)
+}
+
+/// Whether `entry` belongs to a store that keeps arbitrary attributes.
+pub(crate) fn keeps_arbitrary(entry: &Entry) -> bool {
+  let any = entry.as_any();
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if any.is::<crate::secret_service::Cred>() {
+    return true;
+  }
+  any.is::<crate::mock_store::Cred>()
+}
+
+/// Look up the attributes of the credential of `entry` without unlocking it,
+/// or `None` where that takes a read like [get_attributes], i.e. on stores
+/// other than the Secret Service and the mock.
+///
+/// Writes use this to see what they replace. The mock's injected failures
+/// are left for the operations they were meant for.
+pub(crate) fn peek(entry: &Entry) -> Option<Result<HashMap<String, String>>> {
+  if let Some(cred) = entry.as_any().downcast_ref::<crate::mock_store::Cred>() {
+    return Some(
+      cred
+        .stored()
+        .map(|stored| stored.attributes.unwrap_or_default()),
+    );
+  }
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Some(cred) = entry.as_any().downcast_ref::<crate::secret_service::Cred>() {
+    return match cred.peek_attributes() {
+      Ok(mut found) if found.len() == 1 => Some(Ok(found.remove(0))),
+      Ok(found) if found.is_empty() => Some(Err(keyring_core::Error::NoEntry)),
+      // Let the read report the ambiguity.
+      Ok(_) => None,
+      Err(err) => Some(Err(err)),
+    };
+  }
+  None
+}
+
+/// Update the attributes the store keeps with the credential of `entry`.
+///
+/// Attributes that identify the credential are read-only and rejected with
+/// an [Invalid](keyring_core::Error::Invalid) error before anything is written.
+pub(crate) fn update_attributes(entry: &Entry, attributes: &HashMap<String, String>) -> Result<()> {
+  #[cfg(target_os = "linux")]
+  if entry
+    .as_any()
+    .downcast_ref::<linux_keyutils_keyring_store::Cred>()
+    .is_some()
+  {
+    if let Some(key) = attributes.keys().next() {
+      let reason = if key == DESCRIPTION {
+        "cannot be updated"
+      } else {
+        "is not supported, keyutils keys only have a description"
+      };
+      return Err(keyring_core::Error::Invalid(key.clone(), reason.to_owned()));
+    }
+    // Nothing to write, but fail like an update would if the key is missing.
+    return entry.get_secret().map(|_| ());
+  }
+  #[allow(unused_mut)]
+  let mut attributes: HashMap<&str, &str> = attributes
+    .iter()
+    .map(|(key, value)| (key.as_str(), value.as_str()))
+    .collect();
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if secret_service::is_item(entry) {
+    let label = attributes.remove(LABEL);
+    // Write the attributes first, so read-only ones fail before the label is changed.
+    if label.is_none() || !attributes.is_empty() {
+      entry.update_attributes(&attributes)?;
+    }
+    return match label {
+      Some(label) => secret_service::set_label(entry, label),
+      None => Ok(()),
+    };
+  }
+  entry.update_attributes(&attributes)
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+mod secret_service {
+  use keyring_core::{Entry, Result};
+
+  use crate::secret_service::Cred;
+
+  pub(super) fn is_item(entry: &Entry) -> bool {
+    entry.as_any().is::<Cred>()
+  }
+
+  pub(super) fn get_label(entry: &Entry) -> Option<Result<String>> {
+    entry.as_any().downcast_ref::<Cred>().map(Cred::get_label)
+  }
+
+  pub(super) fn set_label(entry: &Entry, label: &str) -> Result<()> {
+    match entry.as_any().downcast_ref::<Cred>() {
+      Some(cred) => cred.set_label(label),
+      None => Ok(()),
+    }
+  }
+}
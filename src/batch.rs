@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::async_entry::AsyncOptions;
+use crate::chunked;
+use crate::error::{ErrorCode, to_js_error, to_napi_error};
+use crate::expiry;
+use crate::search::CredentialError;
+use crate::specifier::build_entry;
+use crate::store::ensure_default_store;
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+use crate::store::{Backend, active_store};
+use crate::worker::WorkerTask;
+
+#[napi(object)]
+/// A credential to read or delete in a batch.
+pub struct CredentialId {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+}
+
+#[napi(object)]
+/// A password to write in a batch.
+pub struct PasswordItem {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+  pub password: String,
+}
+
+#[napi(object)]
+/// The outcome of reading one password of a batch.
+pub struct PasswordResult {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+  /// The password, unless it could not be read or decoded.
+  pub password: Option<String>,
+  /// Why the password could not be read or decoded.
+  pub error: Option<CredentialError>,
+}
+
+#[napi(object)]
+/// The outcome of writing or deleting one credential of a batch.
+pub struct BatchResult {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+  /// Why the credential could not be written or deleted.
+  pub error: Option<CredentialError>,
+}
+
+/// A secret read in bulk, with the attributes of its credential.
+type Contents = (Vec<u8>, HashMap<String, String>);
+
+/// Read the secrets of `ids` in bulk where the store can, leaving the rest
+/// as `None` to be read one by one.
+fn bulk_secrets(ids: &[CredentialId]) -> Vec<Option<keyring_core::Result<Contents>>> {
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Ok((Backend::SecretService, _)) = active_store() {
+    let specs: Vec<_> = ids
+      .iter()
+      .map(|id| (id.service.as_str(), id.user.as_str(), id.target.as_deref()))
+      .collect();
+    // A failed batch fails every credential rather than prompting for each.
+    return match crate::secret_service::get_secrets(&specs) {
+      Ok(found) => found,
+      Err(err) => specs
+        .iter()
+        .map(|_| Some(Err(crate::secret_service::copy_error(&err))))
+        .collect(),
+    };
+  }
+  ids.iter().map(|_| None).collect()
+}
+
+fn get_passwords_(ids: Vec<CredentialId>) -> Vec<PasswordResult> {
+  let secrets = bulk_secrets(&ids);
+  ids
+    .into_iter()
+    .zip(secrets)
+    .map(|(id, secret)| {
+      let password = build_entry(&id.service, &id.user, id.target.as_deref())
+        .and_then(|entry| match secret {
+          // Secrets read in bulk can have expired, or be the manifest of a chunked secret.
+          Some(contents) => contents.and_then(|(secret, mut attributes)| {
+            expiry::check(&entry, &mut attributes)?;
+            chunked::reassemble(&entry, secret)
+          }),
+          None => expiry::get_secret(&entry),
+        })
+        .and_then(|secret| {
+          String::from_utf8(secret)
+            .map_err(|err| keyring_core::Error::BadEncoding(err.into_bytes()))
+        });
+      let (password, error) = match password {
+        Ok(password) => (Some(password), None),
+        Err(err) => (None, Some(err.into())),
+      };
+      PasswordResult {
+        service: id.service,
+        user: id.user,
+        target: id.target,
+        password,
+        error,
+      }
+    })
+    .collect()
+}
+
+fn set_passwords_(items: Vec<PasswordItem>) -> Vec<BatchResult> {
+  items
+    .into_iter()
+    .map(|item| {
+      let result =
+        build_entry(&item.service, &item.user, item.target.as_deref()).and_then(|entry| {
+          expiry::set(&entry, None, || {
+            chunked::set_password(&entry, &item.password)
+          })
+        });
+      BatchResult {
+        service: item.service,
+        user: item.user,
+        target: item.target,
+        error: result.err().map(Into::into),
+      }
+    })
+    .collect()
+}
+
+fn delete_credentials_(ids: Vec<CredentialId>) -> Vec<BatchResult> {
+  ids
+    .into_iter()
+    .map(|id| {
+      let result = build_entry(&id.service, &id.user, id.target.as_deref())
+        .and_then(|entry| chunked::delete_credential(&entry));
+      BatchResult {
+        service: id.service,
+        user: id.user,
+        target: id.target,
+        error: result.err().map(Into::into),
+      }
+    })
+    .collect()
+}
+
+#[napi]
+/// Read the passwords of several credentials at once.
+///
+/// Each credential gets its own result, with an `error` instead of a
+/// `password` if it could not be read or decoded. On the Secret Service,
+/// locked credentials are unlocked with a single prompt and the secrets are
+/// read with one call. Expired credentials are deleted and reported with a
+/// `NoEntry` error, as by `Entry.getPassword`.
+pub fn get_passwords(env: Env, ids: Vec<CredentialId>) -> Result<Vec<PasswordResult>, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  Ok(get_passwords_(ids))
+}
+
+#[napi(ts_return_type = "Promise<Array<PasswordResult>>")]
+/// Read the passwords of several credentials at once, see `getPasswords`.
+pub fn get_passwords_async(
+  env: Env,
+  ids: Vec<CredentialId>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<Batch> {
+  batch(&env, BatchKind::Get(ids), signal, options)
+}
+
+#[napi]
+/// Write the passwords of several credentials at once.
+///
+/// Each credential gets its own result, with an `error` if it could not be
+/// written. As with `Entry.setPassword` without `ttlSeconds`, the credentials
+/// no longer expire.
+pub fn set_passwords(env: Env, items: Vec<PasswordItem>) -> Result<Vec<BatchResult>, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  Ok(set_passwords_(items))
+}
+
+#[napi(ts_return_type = "Promise<Array<BatchResult>>")]
+/// Write the passwords of several credentials at once, see `setPasswords`.
+pub fn set_passwords_async(
+  env: Env,
+  items: Vec<PasswordItem>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<Batch> {
+  batch(&env, BatchKind::Set(items), signal, options)
+}
+
+#[napi]
+/// Delete several credentials at once.
+///
+/// Each credential gets its own result, with an `error` if it could not be
+/// deleted, e.g. a `NoEntry` error if there was none.
+pub fn delete_credentials(env: Env, ids: Vec<CredentialId>) -> Result<Vec<BatchResult>, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  Ok(delete_credentials_(ids))
+}
+
+#[napi(ts_return_type = "Promise<Array<BatchResult>>")]
+/// Delete several credentials at once, see `deleteCredentials`.
+pub fn delete_credentials_async(
+  env: Env,
+  ids: Vec<CredentialId>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<Batch> {
+  batch(&env, BatchKind::Delete(ids), signal, options)
+}
+
+fn batch(
+  env: &Env,
+  kind: BatchKind,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<Batch> {
+  WorkerTask::with_optional_signal(
+    Batch {
+      kind: Some(kind),
+      setup: ensure_default_store(env).err(),
+    },
+    signal,
+  )
+  .with_timeout(options.and_then(|options| options.timeout_ms))
+}
+
+enum BatchKind {
+  Get(Vec<CredentialId>),
+  Set(Vec<PasswordItem>),
+  Delete(Vec<CredentialId>),
+}
+
+pub enum BatchOutput {
+  Passwords(Vec<PasswordResult>),
+  Results(Vec<BatchResult>),
+}
+
+pub struct Batch {
+  kind: Option<BatchKind>,
+  /// Failure to set up the store, reported when the task runs.
+  setup: Option<keyring_core::Error>,
+}
+
+#[napi]
+impl Task for Batch {
+  type Output = keyring_core::Result<BatchOutput>;
+  type JsValue = Either<Vec<PasswordResult>, Vec<BatchResult>>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    if let Some(err) = self.setup.take() {
+      return Ok(Err(err));
+    }
+    Ok(Ok(match self.kind.take().expect("A batch runs once") {
+      BatchKind::Get(ids) => BatchOutput::Passwords(get_passwords_(ids)),
+      BatchKind::Set(items) => BatchOutput::Results(set_passwords_(items)),
+      BatchKind::Delete(ids) => BatchOutput::Results(delete_credentials_(ids)),
+    }))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    match output {
+      Ok(BatchOutput::Passwords(results)) => Ok(Either::A(results)),
+      Ok(BatchOutput::Results(results)) => Ok(Either::B(results)),
+      Err(err) => Err(to_js_error(&env, err)),
+    }
+  }
+}
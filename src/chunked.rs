@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use keyring_core::{Entry, Error, Result};
+use sha2::{Digest, Sha256};
+
+use crate::attributes;
+use crate::exists::{CredentialState, exists};
+#[cfg(target_os = "linux")]
+use crate::keyutils_store::{
+  BIG_KEY_MAX_LEN, USER_KEY_MAX_LEN, find_key, keyutils_error, read_key, remove_big_key,
+  set_big_key,
+};
+use crate::specifier::{build_entry, target_of};
+
+/// What the secret of a chunked credential starts with, followed by the
+/// fields of its [Manifest].
+const MANIFEST_PREFIX: &str = "napi-keyring-chunks/v1 ";
+
+/// The attribute that marks a credential whose secret is a manifest, on
+/// stores that keep attributes, so writes and deletes only read the secret
+/// to find the chunks it replaces when there are any. Empty once a whole
+/// secret replaced the manifest.
+///
+/// It is only a hint: reads go by the secret, which is only ever a
+/// manifest if it was written as one, see [set].
+const CHUNKED: &str = "napi-keyring-chunked";
+
+/// What the user of the credential that stands in for [CHUNKED] ends with,
+/// on stores that keep no attributes, e.g. `alice#chunks`. Writes and
+/// deletes only look for it, without reading any secret.
+const MARKER_SUFFIX: &str = "#chunks";
+
+/// What separates the user of a chunked credential from the generation and
+/// index of each chunk, e.g. `alice#chunk.186f0c2a9e3b1d4000000002.0`.
+const CHUNK_MARKER: &str = "#chunk.";
+
+/// Written in place of a secret that was split into chunks.
+///
+/// Each chunk is a credential of its own, with the service and target of
+/// the chunked one and a user made of its user, the generation and the
+/// index of the chunk.
+struct Manifest {
+  /// Tells the chunks of successive writes apart, so the new chunks are
+  /// complete before the manifest points to them and the old ones go.
+  generation: String,
+  count: usize,
+  len: usize,
+  /// The SHA-256 of the whole secret, in hex.
+  checksum: String,
+}
+
+impl Manifest {
+  fn parse(secret: &[u8]) -> Option<Self> {
+    if let Some(manifest) = std::str::from_utf8(secret).ok().and_then(Self::parse_str) {
+      return Some(manifest);
+    }
+    // The Credential Manager stores passwords, and so manifests, as UTF-16.
+    #[cfg(target_os = "windows")]
+    if secret.len() % 2 == 0 {
+      let units: Vec<u16> = secret
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+      return String::from_utf16(&units)
+        .ok()
+        .as_deref()
+        .and_then(Self::parse_str);
+    }
+    None
+  }
+
+  fn parse_str(text: &str) -> Option<Self> {
+    let mut fields = text.strip_prefix(MANIFEST_PREFIX)?.split(' ');
+    let manifest = Manifest {
+      generation: fields.next()?.to_owned(),
+      count: fields.next()?.parse().ok()?,
+      len: fields.next()?.parse().ok()?,
+      checksum: fields.next()?.to_owned(),
+    };
+    fields.next().is_none().then_some(manifest)
+  }
+
+  /// The entries of the chunks of the credential of `entry`.
+  fn chunks(&self, entry: &Entry) -> Result<Vec<Entry>> {
+    let (service, user) = entry.get_specifiers().ok_or_else(|| {
+      Error::NotSupportedByStore("chunked credentials need a service and user".to_owned())
+    })?;
+    let target = target_of(entry);
+    (0..self.count)
+      .map(|index| {
+        let user = format!("{user}{CHUNK_MARKER}{}.{index}", self.generation);
+        build_entry(&service, &user, target.as_deref())
+      })
+      .collect()
+  }
+
+  fn corrupt(&self, reason: String) -> Error {
+    Error::BadDataFormat(self.to_string().into_bytes(), reason.into())
+  }
+}
+
+impl fmt::Display for Manifest {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{MANIFEST_PREFIX}{} {} {} {}",
+      self.generation, self.count, self.len, self.checksum
+    )
+  }
+}
+
+/// Whether `user` is the user of a chunk or a [MARKER_SUFFIX] credential
+/// rather than of a credential.
+pub(crate) fn is_chunk(user: &str) -> bool {
+  if user.len() > MARKER_SUFFIX.len() && user.ends_with(MARKER_SUFFIX) {
+    return true;
+  }
+  user.rsplit_once(CHUNK_MARKER).is_some_and(|(_, suffix)| {
+    suffix.split_once('.').is_some_and(|(generation, index)| {
+      !generation.is_empty()
+        && generation.chars().all(|c| c.is_ascii_hexdigit())
+        && !index.is_empty()
+        && index.chars().all(|c| c.is_ascii_digit())
+    })
+  })
+}
+
+/// Write `secret` for `entry`, see [set].
+pub(crate) fn set_secret(entry: &Entry, secret: &[u8]) -> Result<()> {
+  set(entry, secret, || entry.set_secret(secret))
+}
+
+/// Write `password` for `entry`, see [set].
+pub(crate) fn set_password(entry: &Entry, password: &str) -> Result<()> {
+  set(entry, password.as_bytes(), || entry.set_password(password))
+}
+
+/// Write `secret` with `write`, or split it into chunks if it is too long
+/// for the store, and remove the chunks of the secret it replaces.
+///
+/// keyutils secrets that don't fit a `user` key go to a `big_key`, and are
+/// only split if the kernel has no `big_key` support or they don't fit
+/// either. Other stores split secrets they reject with a
+/// [TooLong](Error::TooLong) error into chunks of the length it reports.
+/// A secret that reads like a manifest is written as a single chunk, so
+/// reads never mistake it for one.
+fn set(entry: &Entry, secret: &[u8], write: impl FnOnce() -> Result<()>) -> Result<()> {
+  let previous = previous(entry)?;
+  let split = write_whole_or_split(entry, secret, write)?;
+  if let Some(previous) = previous {
+    remove_chunks(entry, &previous);
+    if !split {
+      // A stale mark only costs the next write a read.
+      let _ = mark(entry, false);
+    }
+  }
+  Ok(())
+}
+
+/// Write `secret`, and tell whether it was split.
+fn write_whole_or_split(
+  entry: &Entry,
+  secret: &[u8],
+  write: impl FnOnce() -> Result<()>,
+) -> Result<bool> {
+  let ambiguous = Manifest::parse(secret).is_some();
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = keyutils_cred(entry) {
+    let split = if ambiguous || secret.len() > BIG_KEY_MAX_LEN {
+      write_chunks(entry, secret, USER_KEY_MAX_LEN)?;
+      true
+    } else if secret.len() <= USER_KEY_MAX_LEN {
+      write()?;
+      false
+    } else if set_big_key(cred, secret)? {
+      return Ok(false);
+    } else {
+      write_chunks(entry, secret, USER_KEY_MAX_LEN)?;
+      true
+    };
+    // Reads would still find a big key written before.
+    return remove_big_key(cred).map(|()| split);
+  }
+  let written = if ambiguous {
+    // In a single chunk, unless the store reports a limit.
+    write_chunks(entry, secret, usize::MAX)
+  } else {
+    write()
+  };
+  match written {
+    Err(Error::TooLong(attribute, max)) if max > 0 && names_secret(&attribute) => {
+      write_chunks(entry, secret, max as usize).map(|()| true)
+    }
+    result => result.map(|()| ambiguous),
+  }
+}
+
+/// Whether the attribute a [TooLong](Error::TooLong) error names is the
+/// secret, rather than one that chunks would not make shorter, like the user.
+fn names_secret(attribute: &str) -> bool {
+  // The Credential Manager reports a "password encoded as UTF-16".
+  attribute == "secret" || attribute.starts_with("password")
+}
+
+/// Write `secret` in chunks of at most `max` bytes, then the manifest.
+///
+/// Chunks written before a failure are removed again.
+fn write_chunks(entry: &Entry, secret: &[u8], max: usize) -> Result<()> {
+  let manifest = Manifest {
+    generation: generation(),
+    count: secret.len().div_ceil(max),
+    len: secret.len(),
+    checksum: checksum(secret),
+  };
+  let too_long = || Error::TooLong("secret".to_owned(), max as u32);
+  if entry.get_specifiers().is_none() || manifest.to_string().len() > max {
+    return Err(too_long());
+  }
+  let chunks = manifest.chunks(entry)?;
+  let result = chunks
+    .iter()
+    .zip(secret.chunks(max))
+    .try_for_each(|(chunk, part)| chunk.set_secret(part))
+    .and_then(|()| entry.set_password(&manifest.to_string()));
+  if result.is_err() {
+    remove_chunks(entry, &manifest);
+    return result;
+  }
+  mark(entry, true)
+}
+
+/// Read the secret of `entry`, reassembling it if it was split into chunks.
+pub(crate) fn get_secret(entry: &Entry) -> Result<Vec<u8>> {
+  reassemble(entry, read(entry)?)
+}
+
+/// Read the password of `entry`, reassembling it if it was split into chunks.
+pub(crate) fn get_password(entry: &Entry) -> Result<String> {
+  #[cfg(target_os = "linux")]
+  if keyutils_cred(entry).is_some() {
+    return get_secret(entry).and_then(decode);
+  }
+  let password = entry.get_password()?;
+  match Manifest::parse(password.as_bytes()) {
+    Some(manifest) => assemble(entry, &manifest).and_then(decode),
+    None => Ok(password),
+  }
+}
+
+/// Turn the `secret` read for `entry` into the one that was written, if it
+/// is the manifest of a chunked secret.
+pub(crate) fn reassemble(entry: &Entry, secret: Vec<u8>) -> Result<Vec<u8>> {
+  match Manifest::parse(&secret) {
+    Some(manifest) => assemble(entry, &manifest),
+    None => Ok(secret),
+  }
+}
+
+/// Delete the credential of `entry`, along with its chunks.
+pub(crate) fn delete_credential(entry: &Entry) -> Result<()> {
+  let previous = previous(entry)?;
+  delete(entry)?;
+  if let Some(previous) = previous {
+    remove_chunks(entry, &previous);
+    if let Ok(Some(marker)) = marker(entry) {
+      let _ = marker.delete_credential();
+    }
+  }
+  Ok(())
+}
+
+/// The entries of the chunks of the credential of `entry`, and of its
+/// [MARKER_SUFFIX] credential, if it was split.
+#[cfg(target_os = "linux")]
+pub(crate) fn chunks(entry: &Entry) -> Result<Vec<Entry>> {
+  let Some(manifest) = manifest(entry)? else {
+    return Ok(Vec::new());
+  };
+  let mut chunks = manifest.chunks(entry)?;
+  chunks.extend(marker(entry)?);
+  Ok(chunks)
+}
+
+fn assemble(entry: &Entry, manifest: &Manifest) -> Result<Vec<u8>> {
+  let mut secret = Vec::with_capacity(manifest.len);
+  for (index, chunk) in manifest.chunks(entry)?.iter().enumerate() {
+    match chunk.get_secret() {
+      Ok(part) => secret.extend(part),
+      Err(Error::NoEntry) => return Err(manifest.corrupt(format!("chunk {index} is missing"))),
+      Err(err) => return Err(err),
+    }
+  }
+  if secret.len() != manifest.len || checksum(&secret) != manifest.checksum {
+    return Err(manifest.corrupt("the chunks don't match the checksum".to_owned()));
+  }
+  Ok(secret)
+}
+
+/// Take the attributes this module keeps for itself out of `attributes`.
+pub(crate) fn hide(attributes: &mut HashMap<String, String>) {
+  attributes.remove(CHUNKED);
+}
+
+/// The manifest stored for `entry`, if its secret was split, read only if
+/// its [CHUNKED] attribute, or its [MARKER_SUFFIX] credential on stores
+/// that keep no attributes, says it was.
+fn previous(entry: &Entry) -> Result<Option<Manifest>> {
+  match attributes::peek(entry) {
+    Some(Ok(attributes)) if attributes.get(CHUNKED).is_some_and(|flag| !flag.is_empty()) => {
+      manifest(entry)
+    }
+    Some(Ok(_)) | Some(Err(Error::NoEntry)) => Ok(None),
+    Some(Err(err)) => Err(err),
+    None => match marker(entry)? {
+      Some(marker) if exists(&marker)? != CredentialState::Absent => {
+        let manifest = manifest(entry)?;
+        if manifest.is_none() {
+          // The credential was replaced or deleted behind this module's back.
+          let _ = marker.delete_credential();
+        }
+        Ok(manifest)
+      }
+      _ => Ok(None),
+    },
+  }
+}
+
+/// Set or clear the [CHUNKED] attribute of `entry`, or its [MARKER_SUFFIX]
+/// credential on stores that keep no attributes.
+fn mark(entry: &Entry, chunked: bool) -> Result<()> {
+  if attributes::keeps_arbitrary(entry) {
+    let flag = if chunked { "true" } else { "" };
+    return entry.update_attributes(&[(CHUNKED, flag)].into());
+  }
+  let Some(marker) = marker(entry)? else {
+    return Ok(());
+  };
+  if chunked {
+    return marker.set_secret(b"true");
+  }
+  match marker.delete_credential() {
+    Err(Error::NoEntry) => Ok(()),
+    result => result,
+  }
+}
+
+/// The [MARKER_SUFFIX] credential of `entry`, on stores that keep no
+/// attributes, if `entry` has a service and user to name it after.
+fn marker(entry: &Entry) -> Result<Option<Entry>> {
+  if attributes::keeps_arbitrary(entry) {
+    return Ok(None);
+  }
+  let Some((service, user)) = entry.get_specifiers() else {
+    return Ok(None);
+  };
+  let user = format!("{user}{MARKER_SUFFIX}");
+  build_entry(&service, &user, target_of(entry).as_deref()).map(Some)
+}
+
+/// The manifest stored for `entry`, if its secret was split.
+fn manifest(entry: &Entry) -> Result<Option<Manifest>> {
+  match read(entry) {
+    Ok(secret) => Ok(Manifest::parse(&secret)),
+    Err(Error::NoEntry) => Ok(None),
+    Err(err) => Err(err),
+  }
+}
+
+/// Read the secret stored for `entry`, without reassembling it.
+fn read(entry: &Entry) -> Result<Vec<u8>> {
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = keyutils_cred(entry) {
+    return read_key(&find_key(cred)?);
+  }
+  entry.get_secret()
+}
+
+fn delete(entry: &Entry) -> Result<()> {
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = keyutils_cred(entry) {
+    // The store only finds `user` keys, not big keys.
+    return find_key(cred)?.invalidate().map_err(keyutils_error);
+  }
+  entry.delete_credential()
+}
+
+/// Delete the chunks of `manifest`, as far as possible: they are unreachable
+/// once the manifest is gone, so failing here would only fail a write or
+/// delete that happened.
+fn remove_chunks(entry: &Entry, manifest: &Manifest) {
+  for chunk in manifest.chunks(entry).unwrap_or_default() {
+    let _ = chunk.delete_credential();
+  }
+}
+
+fn decode(secret: Vec<u8>) -> Result<String> {
+  String::from_utf8(secret).map_err(|err| Error::BadEncoding(err.into_bytes()))
+}
+
+fn checksum(secret: &[u8]) -> String {
+  Sha256::digest(secret)
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+/// A generation that differs between writes, from the time of the write
+/// and a counter for writes of this process within the same tick.
+fn generation() -> String {
+  static WRITES: AtomicU32 = AtomicU32::new(0);
+  let elapsed = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default();
+  let write = WRITES.fetch_add(1, Ordering::Relaxed);
+  format!("{:x}{write:08x}", elapsed.as_nanos())
+}
+
+#[cfg(target_os = "linux")]
+fn keyutils_cred(entry: &Entry) -> Option<&linux_keyutils_keyring_store::Cred> {
+  entry.as_any().downcast_ref()
+}
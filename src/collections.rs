@@ -0,0 +1,207 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::async_entry::AsyncOptions;
+use crate::error::{ErrorCode, deleted, to_js_error, to_napi_error};
+use crate::worker::WorkerTask;
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+use crate::secret_service::{
+  collection_by_alias as by_alias, create_collection as create, delete_collection as delete,
+  list_collections as list,
+};
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+use unsupported::{by_alias, create, delete, list};
+
+#[napi(object, object_from_js = false)]
+/// A Secret Service collection, which `Entry.withTarget` refers to by label.
+pub struct Collection {
+  pub label: String,
+  /// The D-Bus object path of the collection.
+  pub path: String,
+  pub locked: bool,
+  /// Whether the `default` alias points to it, i.e. entries without a
+  /// target are written to it.
+  pub is_default: bool,
+}
+
+#[napi]
+/// List the Secret Service collections, without unlocking them.
+///
+/// Throws a `NotSupported` error on platforms without the Secret Service.
+pub fn list_collections() -> Result<Vec<Collection>, ErrorCode> {
+  list().map_err(to_napi_error)
+}
+
+#[napi(ts_return_type = "Promise<Array<Collection>>")]
+/// List the Secret Service collections, see `listCollections`.
+pub fn list_collections_async(
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<ListCollections> {
+  WorkerTask::with_optional_signal(ListCollections, signal)
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+}
+
+#[napi]
+/// Create a Secret Service collection, which may prompt the user for its
+/// password, and return it. `alias`, e.g. `'default'`, is pointed at it.
+///
+/// Throws an `Invalid` error if a collection with this label exists already.
+pub fn create_collection(label: String, alias: Option<String>) -> Result<Collection, ErrorCode> {
+  create(&label, alias.as_deref()).map_err(to_napi_error)
+}
+
+#[napi(ts_return_type = "Promise<Collection>")]
+/// Create a Secret Service collection, see `createCollection`.
+pub fn create_collection_async(
+  label: String,
+  alias: Option<String>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<CreateCollection> {
+  WorkerTask::with_optional_signal(CreateCollection { label, alias }, signal)
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+}
+
+#[napi]
+/// Delete the Secret Service collection with this label, and every item in it.
+///
+/// The `default` collection cannot be deleted. Returns a `NoEntry` error if
+/// there is no such collection.
+pub fn delete_collection(label: String) -> Result<bool, ErrorCode> {
+  deleted(delete(&label)).map_err(to_napi_error)
+}
+
+#[napi(ts_return_type = "Promise<boolean>")]
+/// Delete a Secret Service collection, see `deleteCollection`.
+pub fn delete_collection_async(
+  label: String,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<DeleteCollection> {
+  WorkerTask::with_optional_signal(DeleteCollection { label }, signal)
+    .with_timeout(options.and_then(|options| options.timeout_ms))
+}
+
+#[napi]
+/// The Secret Service collection an alias points to, `'default'` unless
+/// given, or nothing if the alias is not set.
+pub fn get_collection_by_alias(alias: Option<String>) -> Result<Option<Collection>, ErrorCode> {
+  by_alias(alias.as_deref().unwrap_or("default")).map_err(to_napi_error)
+}
+
+#[napi(ts_return_type = "Promise<Collection | undefined>")]
+/// The Secret Service collection an alias points to, see `getCollectionByAlias`.
+pub fn get_collection_by_alias_async(
+  alias: Option<String>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<CollectionByAlias> {
+  WorkerTask::with_optional_signal(
+    CollectionByAlias {
+      alias: alias.unwrap_or_else(|| "default".to_owned()),
+    },
+    signal,
+  )
+  .with_timeout(options.and_then(|options| options.timeout_ms))
+}
+
+pub struct ListCollections;
+
+#[napi]
+impl Task for ListCollections {
+  type Output = keyring_core::Result<Vec<Collection>>;
+  type JsValue = Vec<Collection>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(list())
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
+  }
+}
+
+pub struct CreateCollection {
+  label: String,
+  alias: Option<String>,
+}
+
+#[napi]
+impl Task for CreateCollection {
+  type Output = keyring_core::Result<Collection>;
+  type JsValue = Collection;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(create(&self.label, self.alias.as_deref()))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
+  }
+}
+
+pub struct DeleteCollection {
+  label: String,
+}
+
+#[napi]
+impl Task for DeleteCollection {
+  type Output = keyring_core::Result<()>;
+  type JsValue = bool;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(delete(&self.label))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    deleted(output).map_err(|err| to_js_error(&env, err))
+  }
+}
+
+pub struct CollectionByAlias {
+  alias: String,
+}
+
+#[napi]
+impl Task for CollectionByAlias {
+  type Output = keyring_core::Result<Option<Collection>>;
+  type JsValue = Option<Collection>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(by_alias(&self.alias))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|err| to_js_error(&env, err))
+  }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+mod unsupported {
+  use keyring_core::{Error, Result};
+
+  use super::Collection;
+
+  fn not_supported() -> Error {
+    Error::NotSupportedByStore("collections are a Secret Service feature".to_owned())
+  }
+
+  pub(super) fn list() -> Result<Vec<Collection>> {
+    Err(not_supported())
+  }
+
+  pub(super) fn create(_label: &str, _alias: Option<&str>) -> Result<Collection> {
+    Err(not_supported())
+  }
+
+  pub(super) fn delete(_label: &str) -> Result<()> {
+    Err(not_supported())
+  }
+
+  pub(super) fn by_alias(_alias: &str) -> Result<Option<Collection>> {
+    Err(not_supported())
+  }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use keyring_core::{Entry, Error, Result};
+
+use crate::chunked;
+use crate::expiry;
+use crate::specifier::target_of;
+
+/// Set the password of `entry` to `next` if its current password is
+/// `expected`, where `None` means there is no credential. Returns whether it
+/// was written.
+///
+/// Passwords are read and written like `getPassword` and `setPassword` do,
+/// so they compare equal whatever encoding the store keeps them in.
+///
+/// Like any write, it makes the credential expire after `ttl_seconds`, or
+/// never if that is `None`, see [expiry::set].
+///
+/// The read and the write are serialized with every other conditional write
+/// to the same credential, in this process and in any other process that
+/// uses this module.
+pub(crate) fn set_password_if(
+  entry: &Entry,
+  expected: Option<&str>,
+  next: &str,
+  ttl_seconds: Option<u32>,
+) -> Result<bool> {
+  write_if(
+    entry,
+    ttl_seconds,
+    || Ok(current(expiry::get_password(entry))?.as_deref() == expected),
+    || chunked::set_password(entry, next),
+  )
+}
+
+/// Set the secret of `entry` to `next` if its current secret is `expected`,
+/// like [set_password_if] does for passwords.
+pub(crate) fn set_secret_if(
+  entry: &Entry,
+  expected: Option<&[u8]>,
+  next: &[u8],
+  ttl_seconds: Option<u32>,
+) -> Result<bool> {
+  write_if(
+    entry,
+    ttl_seconds,
+    || Ok(current(expiry::get_secret(entry))?.as_deref() == expected),
+    || chunked::set_secret(entry, next),
+  )
+}
+
+/// Write the credential of `entry` with `write` if `matches` finds its
+/// current value is the expected one, holding its lock throughout.
+fn write_if(
+  entry: &Entry,
+  ttl_seconds: Option<u32>,
+  matches: impl FnOnce() -> Result<bool>,
+  write: impl FnOnce() -> Result<()>,
+) -> Result<bool> {
+  with_lock(entry, || {
+    if !matches()? {
+      return Ok(false);
+    }
+    expiry::set(entry, ttl_seconds, write)?;
+    Ok(true)
+  })
+}
+
+/// The value `read` from a credential, or `None` if there is no credential.
+fn current<T>(read: Result<T>) -> Result<Option<T>> {
+  match read {
+    Ok(value) => Ok(Some(value)),
+    Err(Error::NoEntry) => Ok(None),
+    Err(err) => Err(err),
+  }
+}
+
+/// Run `op` holding the in-process and the cross-process lock of `entry`'s credential.
+fn with_lock<T>(entry: &Entry, op: impl FnOnce() -> Result<T>) -> Result<T> {
+  let key = lock_key(entry);
+  let local = locks().entry(key.clone()).or_default().clone();
+  let result = {
+    let _local = local
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let file = lock_file(&key).map_err(|err| Error::PlatformFailure(Box::new(err)))?;
+    file
+      .lock()
+      .map_err(|err| Error::PlatformFailure(Box::new(err)))?;
+    // The lock is released when the file is closed, even if `op` panics.
+    op()
+  };
+  // Forget the lock once no other call holds or waits for it, so the table
+  // only keeps credentials that are being written.
+  let mut locks = locks();
+  drop(local);
+  if locks
+    .get(&key)
+    .is_some_and(|lock| Arc::strong_count(lock) == 1)
+  {
+    locks.remove(&key);
+  }
+  result
+}
+
+/// One lock per credential, shared by the JS thread and the keyring worker.
+static LOCKS: OnceLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> = OnceLock::new();
+
+fn locks() -> MutexGuard<'static, HashMap<String, Arc<Mutex<()>>>> {
+  LOCKS
+    .get_or_init(Default::default)
+    .lock()
+    .expect("Poisoned lock table, please report a bug")
+}
+
+/// What identifies the credential of `entry` across entries and processes.
+fn lock_key(entry: &Entry) -> String {
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = entry
+    .as_any()
+    .downcast_ref::<linux_keyutils_keyring_store::Cred>()
+  {
+    return format!("keyutils\0{}", cred.description);
+  }
+  let Some((service, user)) = entry.get_specifiers() else {
+    // Wrappers of an existing platform credential have no specifiers.
+    return format!("{entry:?}");
+  };
+  format!(
+    "{service}\0{user}\0{}",
+    target_of(entry).unwrap_or_default()
+  )
+}
+
+/// Open the advisory lock file of `key` in the [lock_dir].
+fn lock_file(key: &str) -> std::io::Result<File> {
+  let mut path = lock_dir()?;
+  path.push(format!("{:016x}.lock", fnv1a(key.as_bytes())));
+  OpenOptions::new()
+    .create(true)
+    .truncate(false)
+    .write(true)
+    .open(path)
+}
+
+/// The directory of the lock files of the current user.
+///
+/// On Unix, that is `$XDG_RUNTIME_DIR/napi-keyring-locks` if it is set, and
+/// otherwise a directory named after the user id in the temporary directory.
+/// It must be a directory of the user that no one else can write to, so
+/// other users can neither hold the locks nor redirect them with symlinks.
+/// Windows gives each user a temporary directory of their own.
+#[cfg(unix)]
+fn lock_dir() -> std::io::Result<PathBuf> {
+  use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+  // SAFETY: `geteuid` cannot fail and has no preconditions.
+  let uid = unsafe { libc::geteuid() };
+  let path = match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+    Some(dir) => PathBuf::from(dir).join("napi-keyring-locks"),
+    None => std::env::temp_dir().join(format!("napi-keyring-locks-{uid}")),
+  };
+  match std::fs::DirBuilder::new().mode(0o700).create(&path) {
+    Err(err) if err.kind() != std::io::ErrorKind::AlreadyExists => return Err(err),
+    _ => {}
+  }
+  // Not followed if it is a symlink, which fails the check below.
+  let metadata = std::fs::symlink_metadata(&path)?;
+  if !metadata.is_dir() || metadata.uid() != uid || metadata.permissions().mode() & 0o022 != 0 {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::PermissionDenied,
+      format!(
+        "{} must be a directory of the current user that only they can write to",
+        path.display()
+      ),
+    ));
+  }
+  Ok(path)
+}
+
+#[cfg(not(unix))]
+fn lock_dir() -> std::io::Result<PathBuf> {
+  let path = std::env::temp_dir().join("napi-keyring-locks");
+  std::fs::create_dir_all(&path)?;
+  Ok(path)
+}
+
+/// A hash that stays the same across processes and builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+  bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+    (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+  })
+}
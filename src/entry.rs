@@ -1,52 +1,34 @@
+use std::collections::HashMap;
+
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-#[cfg(target_os = "linux")]
-use crate::linux_credential_builder::LinuxCredentialBuilder;
+use crate::ambiguity::{self, ResolveOptions};
+use crate::attributes;
+use crate::chunked;
+use crate::conditional;
+use crate::error::{ErrorCode, deleted, optional, to_js_error, to_napi_error};
+use crate::exists::{self, CredentialState};
+use crate::expiry::{self, SetOptions};
+use crate::permissions::{self, KeyPermissions, ShareOptions};
+use crate::specifier::{EntrySpecifier, build_entry, specifier, target_of};
+use crate::store::ensure_default_store;
+use crate::url::{self, UrlOptions};
 
 #[napi]
 pub struct Entry {
   inner: keyring_core::Entry,
+  /// The target the entry was created with, or the one its store records.
+  target: Option<String>,
 }
 
-#[cfg(target_os = "linux")]
-fn setup_linux_store() -> anyhow::Result<()> {
-  let builder = LinuxCredentialBuilder::new()?;
-  keyring_core::set_default_store(builder.get_store());
-  Ok(())
-}
-
-#[cfg(target_os = "macos")]
-fn setup_macos_store() -> anyhow::Result<()> {
-  use std::collections::HashMap;
-
-  use apple_native_keyring_store::keychain::Store;
-
-  let store = Store::new_with_configuration(&HashMap::new())?;
-  keyring_core::set_default_store(store);
-  Ok(())
-}
-
-#[cfg(target_os = "windows")]
-fn setup_windows_store() -> anyhow::Result<()> {
-  use std::collections::HashMap;
-
-  use windows_native_keyring_store::Store;
-
-  let store = Store::new_with_configuration(&HashMap::new())?;
-  keyring_core::set_default_store(store);
-  Ok(())
-}
-
-#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
-fn setup_bsd_store() -> anyhow::Result<()> {
-  use std::collections::HashMap;
-
-  use dbus_secret_service_keyring_store::Store;
-
-  let store = Store::new_with_configuration(&HashMap::new())?;
-  keyring_core::set_default_store(store);
-  Ok(())
+impl From<keyring_core::Entry> for Entry {
+  fn from(inner: keyring_core::Entry) -> Self {
+    Self {
+      target: target_of(&inner),
+      inner,
+    }
+  }
 }
 
 #[napi]
@@ -55,18 +37,12 @@ impl Entry {
   /// Create an entry for the given service and username.
   ///
   /// The default credential builder is used.
-  pub fn new(service: String, username: String) -> Result<Self> {
-    #[cfg(target_os = "linux")]
-    setup_linux_store()?;
-    #[cfg(target_os = "macos")]
-    setup_macos_store()?;
-    #[cfg(target_os = "windows")]
-    setup_windows_store()?;
-    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
-    setup_bsd_store()?;
+  pub fn new(env: Env, service: String, username: String) -> Result<Self, ErrorCode> {
+    ensure_default_store(&env).map_err(to_napi_error)?;
 
     Ok(Self {
-      inner: keyring_core::Entry::new(&service, &username).map_err(anyhow::Error::from)?,
+      inner: build_entry(&service, &username, None).map_err(to_napi_error)?,
+      target: None,
     })
   }
 
@@ -74,26 +50,17 @@ impl Entry {
   /// Create an entry for the given target, service, and username.
   ///
   /// The default credential builder is used.
-  pub fn with_target(target: String, service: String, username: String) -> Result<Self> {
-    #[cfg(target_os = "linux")]
-    setup_linux_store()?;
-    #[cfg(target_os = "macos")]
-    setup_macos_store()?;
-    #[cfg(target_os = "windows")]
-    setup_windows_store()?;
-    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
-    setup_bsd_store()?;
+  pub fn with_target(
+    env: Env,
+    target: String,
+    service: String,
+    username: String,
+  ) -> Result<Self, ErrorCode> {
+    ensure_default_store(&env).map_err(to_napi_error)?;
 
     let entry = Self {
-      inner: keyring_core::Entry::new_with_modifiers(&service, &username, &{
-        let mut mods = std::collections::HashMap::new();
-        #[cfg(target_os = "macos")]
-        mods.insert("keychain", target.as_str());
-        #[cfg(not(target_os = "macos"))]
-        mods.insert("target", target.as_str());
-        mods
-      })
-      .map_err(anyhow::Error::from)?,
+      inner: build_entry(&service, &username, Some(&target)).map_err(to_napi_error)?,
+      target: Some(target.clone()),
     };
 
     // On Windows, when using the target modifier, the username needs to be preserved
@@ -114,6 +81,61 @@ impl Entry {
     Ok(entry)
   }
 
+  #[napi(factory)]
+  /// Create an entry from the specifier returned by `toJSON`.
+  ///
+  /// The default credential builder is used.
+  pub fn from_specifier(env: Env, specifier: EntrySpecifier) -> Result<Self, ErrorCode> {
+    match specifier.target {
+      Some(target) => Self::with_target(env, target, specifier.service, specifier.user),
+      None => Self::new(env, specifier.service, specifier.user),
+    }
+  }
+
+  #[napi(factory)]
+  /// Create an entry for a URL, e.g. `https://user@registry.example.com`.
+  ///
+  /// The service is the URL's origin by default, normalized so that every
+  /// spelling of it, with or without a default port or trailing slash, gives
+  /// the same entry. The user comes from the URL or from `options.user`.
+  pub fn from_url(env: Env, url: String, options: Option<UrlOptions>) -> Result<Self, ErrorCode> {
+    let (service, user) = url::specifiers(&url, options.as_ref()).map_err(to_napi_error)?;
+    match options.and_then(|options| options.target) {
+      Some(target) => Self::with_target(env, target, service, user),
+      None => Self::new(env, service, user),
+    }
+  }
+
+  #[napi(getter)]
+  /// The service of this entry.
+  ///
+  /// `null` for the handles of an `Ambiguous` error on stores that cannot
+  /// tell which service a credential belongs to.
+  pub fn service(&self) -> Option<String> {
+    self.inner.get_specifiers().map(|(service, _)| service)
+  }
+
+  #[napi(getter)]
+  /// The user of this entry, `null` when the `service` is.
+  pub fn user(&self) -> Option<String> {
+    self.inner.get_specifiers().map(|(_, user)| user)
+  }
+
+  #[napi(getter)]
+  /// The target of this entry, if it has one.
+  pub fn target(&self) -> Option<String> {
+    self.target.clone()
+  }
+
+  #[napi(js_name = "toJSON")]
+  /// The specifier of this entry, to be stored or sent to another thread and
+  /// turned back into an entry with `fromSpecifier`.
+  ///
+  /// `null` when the `service` is.
+  pub fn to_json(&self) -> Option<EntrySpecifier> {
+    specifier(&self.inner, self.target.as_deref())
+  }
+
   #[napi]
   /// Set the password for this entry.
   ///
@@ -122,12 +144,20 @@ impl Entry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  pub fn set_password(&self, password: String) -> Result<()> {
-    self
-      .inner
-      .set_password(&password)
-      .map_err(anyhow::Error::from)?;
-    Ok(())
+  ///
+  /// With `ttlSeconds`, the credential is deleted once that many seconds
+  /// have passed, see `getExpiry`. Without it, any expiry is cleared.
+  pub fn set_password(
+    &self,
+    env: Env,
+    password: String,
+    options: Option<SetOptions>,
+  ) -> Result<()> {
+    let ttl_seconds = options.and_then(|options| options.ttl_seconds);
+    expiry::set(&self.inner, ttl_seconds, || {
+      chunked::set_password(&self.inner, &password)
+    })
+    .map_err(|err| to_js_error(&env, err))
   }
 
   #[napi]
@@ -138,27 +168,65 @@ impl Entry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  pub fn set_secret(&self, secret: &[u8]) -> Result<()> {
-    self.inner.set_secret(secret).map_err(anyhow::Error::from)?;
-    Ok(())
+  ///
+  /// Takes the same options as `setPassword`.
+  pub fn set_secret(&self, env: Env, secret: &[u8], options: Option<SetOptions>) -> Result<()> {
+    let ttl_seconds = options.and_then(|options| options.ttl_seconds);
+    expiry::set(&self.inner, ttl_seconds, || {
+      chunked::set_secret(&self.inner, secret)
+    })
+    .map_err(|err| to_js_error(&env, err))
   }
 
   #[napi]
-  /// Retrieve the password saved for this entry.
+  /// Set the password for this entry if its current password is `expected`,
+  /// or if there is no credential when `expected` is `null`.
   ///
-  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  /// Returns whether the password was written. The check and the write are
+  /// serialized with every other conditional write to the same credential,
+  /// in this process and, through an advisory lock file, in other processes
+  /// using this module. Plain writes are not serialized.
   ///
-  /// Can return an [Ambiguous](Error::Ambiguous) error
-  /// if there is more than one platform credential
-  /// that matches this entry.  This can only happen
-  /// on some platforms, and then only if a third-party
-  /// application wrote the ambiguous credential.
-  pub fn get_password(&self) -> Option<String> {
-    self.inner.get_password().ok()
+  /// Takes the same options as `setPassword`: like any write, it clears the
+  /// expiry of the credential unless `ttlSeconds` is given.
+  pub fn set_password_if(
+    &self,
+    env: Env,
+    expected: Option<String>,
+    next: String,
+    options: Option<SetOptions>,
+  ) -> Result<bool> {
+    conditional::set_password_if(
+      &self.inner,
+      expected.as_deref(),
+      &next,
+      options.and_then(|options| options.ttl_seconds),
+    )
+    .map_err(|err| to_js_error(&env, err))
   }
 
   #[napi]
-  /// Retrieve the secret saved for this entry.
+  /// Set the secret for this entry unless there is a credential already.
+  ///
+  /// Returns whether the secret was written, and takes the same options as
+  /// `setPasswordIf`.
+  pub fn set_secret_if_absent(
+    &self,
+    env: Env,
+    secret: &[u8],
+    options: Option<SetOptions>,
+  ) -> Result<bool> {
+    conditional::set_secret_if(
+      &self.inner,
+      None,
+      secret,
+      options.and_then(|options| options.ttl_seconds),
+    )
+    .map_err(|err| to_js_error(&env, err))
+  }
+
+  #[napi]
+  /// Retrieve the password saved for this entry.
   ///
   /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
   ///
@@ -167,12 +235,12 @@ impl Entry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  pub fn get_secret(&self) -> Option<Vec<u8>> {
-    self.inner.get_secret().ok()
+  pub fn get_password(&self, env: Env) -> Result<Option<String>> {
+    optional(expiry::get_password(&self.inner)).map_err(|err| to_js_error(&env, err))
   }
 
   #[napi]
-  /// Delete the underlying credential for this entry.
+  /// Retrieve the secret saved for this entry.
   ///
   /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
   ///
@@ -181,367 +249,131 @@ impl Entry {
   /// that matches this entry.  This can only happen
   /// on some platforms, and then only if a third-party
   /// application wrote the ambiguous credential.
-  ///
-  /// Note: This does _not_ affect the lifetime of the [Entry]
-  /// structure, which is controlled by Rust.  It only
-  /// affects the underlying credential store.
-  pub fn delete_credential(&self) -> bool {
-    self
-      .inner
-      .delete_credential()
-      .map_err(anyhow::Error::from)
-      .is_ok()
+  pub fn get_secret(&self, env: Env) -> Result<Option<Vec<u8>>> {
+    optional(expiry::get_secret(&self.inner)).map_err(|err| to_js_error(&env, err))
   }
 
   #[napi]
-  /// Alias for `deleteCredential`
-  pub fn delete_password(&self) -> bool {
-    self.delete_credential()
-  }
-}
-
-#[napi(object)]
-pub struct Credential {
-  pub account: String,
-  pub password: String,
-}
-
-pub struct FindCredentials {
-  service: String,
-  target: Option<String>,
-}
-
-#[napi]
-impl Task for FindCredentials {
-  type Output = Vec<Credential>;
-  type JsValue = Vec<Credential>;
-
-  #[inline]
-  fn compute(&mut self) -> Result<Self::Output> {
-    Ok(
-      find_credentials_(&self.service, self.target.clone())?
-        .into_iter()
-        .map(|(account, password)| Credential { account, password })
-        .collect(),
-    )
-  }
-
-  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
-    Ok(output)
+  /// Check whether there is a credential for this entry.
+  ///
+  /// Only the store's metadata is consulted: the secret is not read and a
+  /// locked credential is reported as `locked` rather than unlocked.
+  pub fn exists(&self, env: Env) -> Result<CredentialState> {
+    exists::exists(&self.inner).map_err(|err| to_js_error(&env, err))
   }
-}
-
-#[napi]
-/// find credentials by service name
-pub fn find_credentials(service: String, target: Option<String>) -> Result<Vec<Credential>> {
-  Ok(
-    find_credentials_(&service, target)?
-      .into_iter()
-      .map(|(account, password)| Credential { account, password })
-      .collect(),
-  )
-}
-
-#[napi]
-/// find credentials by service name
-pub fn find_credentials_async(
-  service: String,
-  target: Option<String>,
-  signal: Option<AbortSignal>,
-) -> AsyncTask<FindCredentials> {
-  AsyncTask::with_optional_signal(FindCredentials { service, target }, signal)
-}
 
-#[cfg(target_os = "macos")]
-fn find_credentials_(
-  service: &str,
-  _target: Option<String>,
-) -> std::result::Result<Vec<(String, String)>, anyhow::Error> {
-  use std::{ffi::c_void, ptr};
-
-  use core_foundation::{
-    array::CFArray,
-    base::{CFGetTypeID, CFRelease, CFType, FromVoid, TCFType},
-    boolean::CFBoolean,
-    dictionary::CFDictionary,
-    string::CFString,
-  };
-  use security_framework::item::{Reference, SearchResult};
-  use security_framework_sys::{
-    base::errSecItemNotFound,
-    item::{
-      kSecAttrAccount, kSecAttrService, kSecClass, kSecClassGenericPassword, kSecMatchLimit,
-      kSecMatchLimitAll, kSecReturnAttributes, kSecReturnRef,
-    },
-    keychain_item::SecItemCopyMatching,
-  };
-
-  let mut params = Vec::with_capacity(5);
-  let k_service = CFString::new(service);
-  params.push((
-    unsafe { CFString::wrap_under_get_rule(kSecClass) },
-    unsafe { CFType::wrap_under_get_rule(kSecClassGenericPassword.cast()) },
-  ));
-  params.push((
-    unsafe { CFString::wrap_under_get_rule(kSecAttrService) },
-    k_service.as_CFType(),
-  ));
-  params.push((
-    unsafe { CFString::wrap_under_get_rule(kSecMatchLimit) },
-    unsafe { CFString::wrap_under_get_rule(kSecMatchLimitAll).as_CFType() },
-  ));
-  params.push((
-    unsafe { CFString::wrap_under_get_rule(kSecReturnRef) },
-    CFBoolean::true_value().as_CFType(),
-  ));
-  params.push((
-    unsafe { CFString::wrap_under_get_rule(kSecReturnAttributes) },
-    CFBoolean::true_value().as_CFType(),
-  ));
-  let params = CFDictionary::from_CFType_pairs(&params);
-
-  let mut ret = ptr::null();
-  if let Err(err) = cvt(unsafe { SecItemCopyMatching(params.as_concrete_TypeRef(), &mut ret) }) {
-    // not found is acceptable
-    if err.code() == errSecItemNotFound {
-      return Ok(vec![]);
-    }
-    return Err(anyhow::Error::from(err));
-  };
-  if ret.is_null() {
-    //  SecItemCopyMatching returns NULL if no load_* was specified,
-    //  causing a segfault.
-    return Ok(vec![]);
-  }
-  let type_id = unsafe { CFGetTypeID(ret) };
-
-  let mut items = vec![];
-
-  if type_id == CFArray::<CFType>::type_id() {
-    let array: CFArray<CFType> = unsafe { CFArray::wrap_under_create_rule(ret as *mut _) };
-    items.extend(
-      array
-        .iter()
-        .map(|item| unsafe { get_item(item.as_CFTypeRef()) }),
-    );
-  } else {
-    items.push(unsafe { get_item(ret) });
-    // This is a bit janky, but get_item uses wrap_under_get_rule
-    // which bumps the refcount but we want create semantics
-    unsafe { CFRelease(ret) };
+  #[napi]
+  /// The number of seconds until this entry's credential expires, or `null`
+  /// if it never does.
+  ///
+  /// keyutils keys expire in the kernel, which only reports the remaining
+  /// time in its largest whole unit, from seconds up to weeks, so the result
+  /// is rounded down to that unit. On other stores that support expiry, an
+  /// expired credential is deleted the next time it is read.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  pub fn get_expiry(&self, env: Env) -> Result<Option<u32>> {
+    optional(expiry::remaining(&self.inner))
+      .map(Option::flatten)
+      .map_err(|err| to_js_error(&env, err))
   }
-  let found = items
-    .iter()
-    .filter_map(|item| {
-      match item {
-        SearchResult::Ref(Reference::KeychainItem(keychain)) => {
-          let dict: CFDictionary<CFString, *const c_void> =
-            unsafe { CFDictionary::wrap_under_get_rule(keychain.as_CFTypeRef().cast()) };
-          if let Some(account) = dict.find(unsafe { kSecAttrAccount }) {
-            let account = unsafe { CFString::from_void(*account) };
-            let account = account.to_string();
-            if let Ok(password) =
-              security_framework::passwords::get_generic_password(service, &account)
-            {
-              return Some((account.to_string(), unsafe {
-                String::from_utf8_unchecked(password)
-              }));
-            }
-          }
-        }
-        SearchResult::Dict(dict) => {
-          if let Some(account) = dict.find(unsafe { kSecAttrAccount }.cast()) {
-            let account = unsafe { CFString::from_void(*account) };
-            let account = account.to_string();
-            if let Ok(password) =
-              security_framework::passwords::get_generic_password(service, &account)
-            {
-              return Some((account.to_string(), unsafe {
-                String::from_utf8_unchecked(password)
-              }));
-            }
-          }
-        }
-        _ => {}
-      };
-      None
-    })
-    .collect::<Vec<(String, String)>>();
-  Ok(found)
-}
 
-#[cfg(target_os = "macos")]
-unsafe fn get_item(
-  item: core_foundation::base::CFTypeRef,
-) -> security_framework::item::SearchResult {
-  use core_foundation::{
-    base::{CFGetTypeID, TCFType},
-    data::CFData,
-    dictionary::CFDictionary,
-  };
-  use security_framework::os::macos::keychain_item::SecKeychainItem;
-  use security_framework::{
-    certificate::SecCertificate,
-    identity::SecIdentity,
-    item::{Reference, SearchResult},
-    key::SecKey,
-  };
-
-  let type_id = unsafe { CFGetTypeID(item) };
-
-  if type_id == CFData::type_id() {
-    let data = unsafe { CFData::wrap_under_get_rule(item as *mut _) };
-    let mut buf = Vec::new();
-    buf.extend_from_slice(data.bytes());
-    return SearchResult::Data(buf);
+  #[napi]
+  /// Retrieve the attributes the store keeps with this entry's credential.
+  ///
+  /// Secret Service items include their `label`, and keyutils keys, which
+  /// have no attributes, report their `description`.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  pub fn get_attributes(&self, env: Env) -> Result<Option<HashMap<String, String>>> {
+    optional(attributes::get_attributes(&self.inner)).map_err(|err| to_js_error(&env, err))
   }
 
-  if type_id == CFDictionary::<*const u8, *const u8>::type_id() {
-    return SearchResult::Dict(unsafe { CFDictionary::wrap_under_get_rule(item as *mut _) });
+  #[napi]
+  /// Update the attributes the store keeps with this entry's credential.
+  ///
+  /// Attributes that identify the credential, such as the service, user and
+  /// target, cannot be updated and are rejected with an `Invalid` error, as
+  /// is any attribute of a keyutils key. On the Secret Service, `label`
+  /// updates the item's label.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  pub fn update_attributes(&self, env: Env, attributes: HashMap<String, String>) -> Result<()> {
+    attributes::update_attributes(&self.inner, &attributes).map_err(|err| to_js_error(&env, err))
   }
 
-  if type_id == SecKeychainItem::type_id() {
-    return SearchResult::Ref(Reference::KeychainItem(unsafe {
-      SecKeychainItem::wrap_under_get_rule(item as *mut _)
-    }));
+  #[napi]
+  /// Retrieve the permissions of this entry's keyutils key, for each class
+  /// of processes.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one, and a
+  /// `NotSupported` error on other stores.
+  pub fn get_permissions(&self, env: Env) -> Result<Option<KeyPermissions>> {
+    optional(permissions::get(&self.inner)).map_err(|err| to_js_error(&env, err))
   }
 
-  let reference = if type_id == SecCertificate::type_id() {
-    Reference::Certificate(unsafe { SecCertificate::wrap_under_get_rule(item as *mut _) })
-  } else if type_id == SecKey::type_id() {
-    Reference::Key(unsafe { SecKey::wrap_under_get_rule(item as *mut _) })
-  } else if type_id == SecIdentity::type_id() {
-    Reference::Identity(unsafe { SecIdentity::wrap_under_get_rule(item as *mut _) })
-  } else {
-    // panic!("Got bad type from SecItemCopyMatching: {}", type_id);
-    return SearchResult::Other;
-  };
-
-  SearchResult::Ref(reference)
-}
-
-#[cfg(target_os = "macos")]
-#[allow(non_upper_case_globals)]
-#[inline(always)]
-fn cvt(
-  err: core_foundation::base::OSStatus,
-) -> std::result::Result<(), security_framework::base::Error> {
-  use security_framework_sys::base::errSecSuccess;
-
-  match err {
-    errSecSuccess => Ok(()),
-    err => Err(security_framework::base::Error::from_code(err)),
-  }
-}
-
-#[cfg(target_os = "windows")]
-fn find_credentials_(
-  service: &str,
-  target: Option<String>,
-) -> std::result::Result<Vec<(String, String)>, anyhow::Error> {
-  use byteorder::ByteOrder;
-  use windows::Win32::Foundation::ERROR_NOT_FOUND;
-  use windows::Win32::Security::Credentials::{
-    CRED_ENUMERATE_FLAGS, CRED_TYPE_DOMAIN_PASSWORD, CRED_TYPE_GENERIC, CREDENTIALW,
-    CredEnumerateW, CredFree,
-  };
-  use windows::core::PCWSTR;
-
-  fn to_wstr(s: &str) -> Vec<u16> {
-    s.encode_utf16().chain(std::iter::once(0)).collect()
+  #[napi]
+  /// Set the permissions of this entry's keyutils key. Classes left out of
+  /// `permissions` keep theirs.
+  ///
+  /// Needs the `setattr` permission, so a key that loses it keeps its new
+  /// permissions until it is deleted.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one, and a
+  /// `NotSupported` error on other stores.
+  pub fn set_permissions(&self, env: Env, permissions: KeyPermissions) -> Result<()> {
+    permissions::set(&self.inner, &permissions).map_err(|err| to_js_error(&env, err))
   }
 
-  fn extract_password(credential: &CREDENTIALW) -> std::result::Result<String, anyhow::Error> {
-    // get password blob
-    let blob_pointer: *const u8 = credential.CredentialBlob;
-    let blob_len: usize = credential.CredentialBlobSize as usize;
-    let blob = unsafe { std::slice::from_raw_parts(blob_pointer, blob_len) };
-    // 3rd parties may write credential data with an odd number of bytes,
-    // so we make sure that we don't try to decode those as utf16
-    if blob.len() % 2 != 0 {
-      return Err(anyhow::anyhow!("Not a valid utf16 blob"));
-    }
-    // Now we know this _can_ be a UTF-16 string, so convert it to
-    // as UTF-16 vector and then try to decode it.
-    let mut blob_u16 = vec![0; blob.len() / 2];
-    byteorder::LittleEndian::read_u16_into(blob, &mut blob_u16);
-    String::from_utf16(&blob_u16).map_err(anyhow::Error::from)
+  #[napi]
+  /// Link this entry's keyutils key to the session keyring, which child
+  /// processes inherit, so entries for the same service and user find it
+  /// there, and return its serial number.
+  ///
+  /// A process without a session keyring joins a new one first, which only
+  /// children spawned afterwards inherit. Children possess the key just like
+  /// this process, so `options.permissions` restrict both: e.g. possessors
+  /// with only `view`, `read` and `search` can read and delete the key, but
+  /// not update it.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one, and a
+  /// `NotSupported` error on other stores.
+  pub fn share_with_children(&self, env: Env, options: Option<ShareOptions>) -> Result<i32> {
+    permissions::share_with_children(&self.inner, options.as_ref())
+      .map_err(|err| to_js_error(&env, err))
   }
 
-  unsafe fn from_wstr(ws: *const u16) -> String {
-    // null pointer case, return empty string
-    if ws.is_null() {
-      return String::new();
-    }
-    // this code from https://stackoverflow.com/a/48587463/558006
-    let len = (0..).take_while(|&i| unsafe { *ws.offset(i) } != 0).count();
-    let slice = unsafe { std::slice::from_raw_parts(ws, len) };
-    String::from_utf16_lossy(slice)
+  #[napi]
+  /// Delete the underlying credential for this entry.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there isn't one.
+  ///
+  /// Can return an [Ambiguous](Error::Ambiguous) error
+  /// if there is more than one platform credential
+  /// that matches this entry.  This can only happen
+  /// on some platforms, and then only if a third-party
+  /// application wrote the ambiguous credential.
+  ///
+  /// Note: This does _not_ affect the lifetime of the [Entry]
+  /// structure, which is controlled by Rust.  It only
+  /// affects the underlying credential store.
+  pub fn delete_credential(&self, env: Env) -> Result<bool> {
+    deleted(chunked::delete_credential(&self.inner)).map_err(|err| to_js_error(&env, err))
   }
 
-  unsafe {
-    let mut count: u32 = 0;
-    let mut p_credentials: *mut *mut CREDENTIALW = std::ptr::null_mut();
-
-    let filter = target.unwrap_or_else(|| format!("*.{service}"));
-    // Enumerate credentials.
-    if let Err(err) = CredEnumerateW(
-      PCWSTR::from_raw(to_wstr(&filter).as_ptr()),
-      Some(CRED_ENUMERATE_FLAGS::default()),
-      &mut count,
-      &mut p_credentials,
-    ) {
-      CredFree(p_credentials.cast());
-      if err == ERROR_NOT_FOUND.to_hresult().into() {
-        return Ok(vec![]);
-      } else {
-        return Err(anyhow::anyhow!("Failed to enumerate credentials {:?}", err));
-      }
-    }
-    let mut credentials_vec: Vec<(String, String)> = vec![];
-    let credentials = std::slice::from_raw_parts(p_credentials, count as usize);
-    for credential in credentials.iter() {
-      if let Some(credential) = credential.as_mut() {
-        if credential.Type != CRED_TYPE_GENERIC && credential.Type != CRED_TYPE_DOMAIN_PASSWORD {
-          continue;
-        }
-
-        let user = from_wstr(credential.UserName.as_ptr().cast());
-        let password = extract_password(credential)?;
-
-        credentials_vec.push((user, password));
-      }
-    }
-
-    CredFree(p_credentials as *mut _);
-    return Ok(credentials_vec);
+  #[napi]
+  /// Delete every credential matching this entry but the one to `keep`.
+  ///
+  /// Returns how many credentials were deleted, which is zero unless the
+  /// entry is ambiguous. `keep` is `'newest'` or `'oldest'` by creation
+  /// time, or an index into the `credentials` of the `Ambiguous` error.
+  pub fn resolve_ambiguity(&self, env: Env, options: ResolveOptions) -> Result<u32> {
+    ambiguity::resolve(&self.inner, &options.keep).map_err(|err| to_js_error(&env, err))
   }
-}
 
-#[cfg(any(target_os = "linux", target_os = "freebsd"))]
-fn find_credentials_(
-  service: &str,
-  _target: Option<String>,
-) -> std::result::Result<Vec<(String, String)>, anyhow::Error> {
-  use std::collections::HashMap;
-
-  use anyhow::Ok;
-  use secret_service::{EncryptionType, SearchItemsResult, blocking::SecretService};
-
-  let mut result = Vec::new();
-  let secret_service = SecretService::connect(EncryptionType::Dh).map_err(anyhow::Error::from)?;
-  let mut attrs = HashMap::with_capacity(1);
-  attrs.insert("service", service);
-  let SearchItemsResult { locked, unlocked } = secret_service
-    .search_items(attrs)
-    .map_err(anyhow::Error::from)?;
-  for item in locked.iter().chain(unlocked.iter()) {
-    let password = item.get_secret().map_err(anyhow::Error::from)?;
-    let password = unsafe { String::from_utf8_unchecked(password) };
-    let attrs = item.get_attributes().map_err(anyhow::Error::from)?;
-    if let Some(user) = attrs.get("username") {
-      result.push((user.clone(), password.to_string()));
-    }
+  #[napi]
+  /// Alias for `deleteCredential`
+  pub fn delete_password(&self, env: Env) -> Result<bool> {
+    self.delete_credential(env)
   }
-  Ok(result)
 }
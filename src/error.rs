@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::entry::Entry;
+
+/// Whether getters and `deleteCredential` swallow errors, see [set_lenient_errors].
+static LENIENT_ERRORS: AtomicBool = AtomicBool::new(false);
+
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Stable `code` carried by every error thrown or rejected by this module.
+pub enum ErrorCode {
+  /// There is no credential in the store that matches the entry.
+  NoEntry,
+  /// More than one credential in the store matches the entry.
+  Ambiguous,
+  /// The store could not be accessed, e.g. because it is locked.
+  NoStorageAccess,
+  /// The underlying platform store failed.
+  PlatformFailure,
+  /// The stored data could not be decoded.
+  BadEncoding,
+  /// An attribute exceeded the platform length limit.
+  TooLong,
+  /// An argument was rejected by the store.
+  Invalid,
+  /// The operation is not supported by the active store.
+  NotSupported,
+  /// An async operation did not finish within its `timeoutMs`.
+  Timeout,
+  /// An async operation was cancelled through its `AbortSignal`.
+  Aborted,
+}
+
+impl AsRef<str> for ErrorCode {
+  fn as_ref(&self) -> &str {
+    match self {
+      ErrorCode::NoEntry => "NoEntry",
+      ErrorCode::Ambiguous => "Ambiguous",
+      ErrorCode::NoStorageAccess => "NoStorageAccess",
+      ErrorCode::PlatformFailure => "PlatformFailure",
+      ErrorCode::BadEncoding => "BadEncoding",
+      ErrorCode::TooLong => "TooLong",
+      ErrorCode::Invalid => "Invalid",
+      ErrorCode::NotSupported => "NotSupported",
+      ErrorCode::Timeout => "Timeout",
+      ErrorCode::Aborted => "Aborted",
+    }
+  }
+}
+
+/// An error whose JS `code` property is an [ErrorCode].
+pub type KeyringError = napi::Error<ErrorCode>;
+
+#[napi]
+/// Restore the lenient behavior of earlier versions.
+///
+/// When enabled, `getPassword` and `getSecret` resolve to `null`/`undefined`
+/// and `deleteCredential` to `false` on any error instead of throwing.
+pub fn set_lenient_errors(enabled: bool) {
+  LENIENT_ERRORS.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn is_lenient() -> bool {
+  LENIENT_ERRORS.load(Ordering::Relaxed)
+}
+
+/// Convert a `keyring_core` error, keeping the platform detail as the `cause`.
+pub(crate) fn to_napi_error(err: keyring_core::Error) -> KeyringError {
+  convert(&err)
+}
+
+fn convert(err: &keyring_core::Error) -> KeyringError {
+  use keyring_core::Error as E;
+
+  let code = match err {
+    E::NoEntry => ErrorCode::NoEntry,
+    E::Ambiguous(_) => ErrorCode::Ambiguous,
+    E::NoStorageAccess(_) => ErrorCode::NoStorageAccess,
+    E::BadEncoding(_) | E::BadDataFormat(..) => ErrorCode::BadEncoding,
+    E::TooLong(..) => ErrorCode::TooLong,
+    E::Invalid(..) => ErrorCode::Invalid,
+    E::NotSupportedByStore(_) => ErrorCode::NotSupported,
+    _ => ErrorCode::PlatformFailure,
+  };
+  let mut error = KeyringError::new(code, err.to_string());
+  if let Some(source) = std::error::Error::source(err) {
+    error.set_cause(Error::new(Status::GenericFailure, source.to_string()));
+  }
+  error
+}
+
+/// Convert a `keyring_core` error into a JS error that keeps its `code`.
+///
+/// An `Ambiguous` error also gets a `credentials` property holding an
+/// `Entry` for every matching credential.
+pub(crate) fn to_js_error(env: &Env, err: keyring_core::Error) -> Error {
+  let error = convert(&err);
+  let keyring_core::Error::Ambiguous(entries) = err else {
+    return into_rejection(env, error);
+  };
+  let credentials: Vec<Entry> = entries.into_iter().map(Entry::from).collect();
+  let object = JsError::from(error).into_unknown(*env);
+  let attached = unsafe { object.cast::<Object>() }
+    .and_then(|mut object| object.set_named_property("credentials", credentials));
+  match attached {
+    Ok(()) => Error::from(object),
+    Err(err) => err,
+  }
+}
+
+/// Map a getter result, honoring [set_lenient_errors].
+pub(crate) fn optional<T>(result: keyring_core::Result<T>) -> keyring_core::Result<Option<T>> {
+  match result {
+    Ok(value) => Ok(Some(value)),
+    Err(_) if is_lenient() => Ok(None),
+    Err(err) => Err(err),
+  }
+}
+
+/// Map a deletion result, honoring [set_lenient_errors].
+pub(crate) fn deleted(result: keyring_core::Result<()>) -> keyring_core::Result<bool> {
+  match result {
+    Ok(()) => Ok(true),
+    Err(_) if is_lenient() => Ok(false),
+    Err(err) => Err(err),
+  }
+}
+
+/// Turn a [KeyringError] into an error that can reject an [AsyncTask]
+/// without losing its `code`.
+pub(crate) fn into_rejection(env: &Env, err: KeyringError) -> Error {
+  Error::from(JsError::from(err).into_unknown(*env))
+}
@@ -0,0 +1,73 @@
+use keyring_core::{Entry, Error, Result};
+use napi_derive::napi;
+
+use crate::expiry;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whether the store has a credential for an entry, see `Entry.exists`.
+pub enum CredentialState {
+  /// There is no matching credential.
+  Absent,
+  /// There is exactly one matching credential.
+  Present,
+  /// There is exactly one matching credential, but it is locked.
+  Locked,
+  /// More than one credential matches the entry.
+  Ambiguous,
+}
+
+/// Look for the credential of `entry` without reading or unlocking it.
+pub(crate) fn exists(entry: &Entry) -> Result<CredentialState> {
+  let state = lookup(entry)?;
+  if state != CredentialState::Present {
+    return Ok(state);
+  }
+  // An expired credential is left for a read or write of the entry to delete.
+  match expiry::has_expired(entry) {
+    Ok(true) | Err(Error::NoEntry) => Ok(CredentialState::Absent),
+    Ok(false) => Ok(state),
+    Err(err) => Err(err),
+  }
+}
+
+fn lookup(entry: &Entry) -> Result<CredentialState> {
+  if let Some(cred) = entry.as_any().downcast_ref::<crate::mock_store::Cred>() {
+    return match cred.stored() {
+      Ok(stored) if stored.locked == Some(true) => Ok(CredentialState::Locked),
+      Ok(_) => Ok(CredentialState::Present),
+      Err(Error::NoEntry) => Ok(CredentialState::Absent),
+      Err(Error::Ambiguous(_)) => Ok(CredentialState::Ambiguous),
+      Err(err) => Err(err),
+    };
+  }
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Some(cred) = entry.as_any().downcast_ref::<crate::secret_service::Cred>() {
+    // The store's own lookup unlocks the item, so count the matches instead.
+    return Ok(match cred.count_items()? {
+      (0, 0) => CredentialState::Absent,
+      (1, 0) => CredentialState::Present,
+      (0, 1) => CredentialState::Locked,
+      _ => CredentialState::Ambiguous,
+    });
+  }
+  // The store only looks for `user` keys, and large secrets are written to big keys.
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = entry
+    .as_any()
+    .downcast_ref::<linux_keyutils_keyring_store::Cred>()
+  {
+    return match crate::keyutils_store::find_key(cred) {
+      Ok(_) => Ok(CredentialState::Present),
+      Err(Error::NoEntry) => Ok(CredentialState::Absent),
+      Err(err) => Err(err),
+    };
+  }
+  // Everywhere else, looking up the credential reads no secret and shows no prompt.
+  match entry.get_credential() {
+    Ok(_) => Ok(CredentialState::Present),
+    Err(Error::NoEntry) => Ok(CredentialState::Absent),
+    Err(Error::Ambiguous(_)) => Ok(CredentialState::Ambiguous),
+    Err(err) => Err(err),
+  }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use keyring_core::{Entry, Error, Result};
+use napi_derive::napi;
+
+use crate::{attributes, chunked};
+
+#[cfg(target_os = "linux")]
+use crate::keyutils_store::{find_key, keyutils_error};
+
+/// The attribute that records when a credential expires on stores without
+/// native expiry, in seconds since the Unix epoch. Empty if it never does.
+///
+/// Stores can't remove attributes, so a cleared expiry stays as an empty
+/// value. Either way it is left out of the attributes reported for the
+/// credential, see [check].
+const EXPIRES_AT: &str = "expires-at";
+
+#[napi(object)]
+/// Options of `Entry.setPassword`, `Entry.setSecret` and the conditional writes.
+pub struct SetOptions {
+  /// Delete the credential after this many seconds. Not supported by the
+  /// macOS Keychain and the Windows Credential Manager.
+  pub ttl_seconds: Option<u32>,
+}
+
+/// Write the credential of `entry` with `write`, then make it expire after
+/// `ttl_seconds`, or never if that is `None`.
+///
+/// keyutils keys get a kernel timeout. Mock and Secret Service credentials
+/// get an [EXPIRES_AT] attribute, and a credential whose attribute cannot be
+/// written is deleted rather than left to live forever. Other stores reject
+/// a TTL with a [NotSupportedByStore](Error::NotSupportedByStore) error:
+/// keychain items and Credential Manager credentials have no attribute to
+/// keep it in short of their user-visible comment.
+pub(crate) fn set(
+  entry: &Entry,
+  ttl_seconds: Option<u32>,
+  write: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+  if ttl_seconds == Some(0) {
+    return Err(Error::Invalid(
+      "ttlSeconds".to_owned(),
+      "must be positive".to_owned(),
+    ));
+  }
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = keyutils_cred(entry) {
+    write()?;
+    // A timeout of zero clears the one a previous write set.
+    let timeout = ttl_seconds.unwrap_or(0) as usize;
+    // The chunks of a large secret must not outlive it.
+    for chunk in chunked::chunks(entry)? {
+      if let Some(cred) = keyutils_cred(&chunk) {
+        find_key(cred)?
+          .set_timeout(timeout)
+          .map_err(keyutils_error)?;
+      }
+    }
+    return find_key(cred)?.set_timeout(timeout).map_err(keyutils_error);
+  }
+  if !attributes::keeps_arbitrary(entry) {
+    if ttl_seconds.is_some() {
+      return Err(Error::NotSupportedByStore(
+        "credentials of this store cannot expire".to_owned(),
+      ));
+    }
+    return write();
+  }
+  write()?;
+  let expires_at = match ttl_seconds {
+    Some(ttl_seconds) => (now() + u64::from(ttl_seconds)).to_string(),
+    None => {
+      let attributes = match attributes::peek(entry) {
+        Some(attributes) => attributes?,
+        None => entry.get_attributes()?,
+      };
+      // Only clear an expiry a previous write set, to spare stores the update.
+      if expires_at(&attributes)?.is_none() {
+        return Ok(());
+      }
+      String::new()
+    }
+  };
+  let result = entry.update_attributes(&[(EXPIRES_AT, expires_at.as_str())].into());
+  if result.is_err() && ttl_seconds.is_some() {
+    let _ = chunked::delete_credential(entry);
+  }
+  result
+}
+
+/// Delete the credential of `entry` if its [EXPIRES_AT] attribute has passed.
+///
+/// Returns a [NoEntry](Error::NoEntry) error if it did, so reads can run
+/// this first and fail as if the credential had never been there. keyutils
+/// keys and credentials of other stores are left alone.
+///
+/// This reads the attributes of the credential, so reads that get them
+/// anyway should [check] those instead.
+pub(crate) fn purge(entry: &Entry) -> Result<()> {
+  if !attributes::keeps_arbitrary(entry) {
+    return Ok(());
+  }
+  check(entry, &mut entry.get_attributes()?)
+}
+
+/// Take the [EXPIRES_AT] attribute out of the `attributes` read for `entry`,
+/// and delete the credential if it has passed, see [purge].
+pub(crate) fn check(entry: &Entry, attributes: &mut HashMap<String, String>) -> Result<()> {
+  if !attributes::keeps_arbitrary(entry) {
+    return Ok(());
+  }
+  if take_expired(attributes)? {
+    chunked::delete_credential(entry)?;
+    return Err(Error::NoEntry);
+  }
+  Ok(())
+}
+
+/// Whether the [EXPIRES_AT] attribute of the credential of `entry` has
+/// passed, looked up without unlocking it where the store allows.
+///
+/// Unlike [purge], the credential is left in place, for lookups that only
+/// report on it.
+pub(crate) fn has_expired(entry: &Entry) -> Result<bool> {
+  if !attributes::keeps_arbitrary(entry) {
+    return Ok(false);
+  }
+  let mut attributes = match attributes::peek(entry) {
+    Some(attributes) => attributes?,
+    None => entry.get_attributes()?,
+  };
+  take_expired(&mut attributes)
+}
+
+/// Take the [EXPIRES_AT] attribute out of `attributes`, and tell whether it has passed.
+pub(crate) fn take_expired(attributes: &mut HashMap<String, String>) -> Result<bool> {
+  let expires_at = expires_at(attributes)?;
+  attributes.remove(EXPIRES_AT);
+  Ok(expires_at.is_some_and(|expires_at| expires_at <= now()))
+}
+
+/// Read the secret of `entry`, reassembling it if it was split into chunks,
+/// unless it has expired, see [purge].
+pub(crate) fn get_secret(entry: &Entry) -> Result<Vec<u8>> {
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Some(cred) = entry.as_any().downcast_ref::<crate::secret_service::Cred>() {
+    // Look the item up once for both its attributes and its secret.
+    if let Some((secret, mut attributes)) = cred.read()? {
+      check(entry, &mut attributes)?;
+      return chunked::reassemble(entry, secret);
+    }
+  }
+  purge(entry)?;
+  chunked::get_secret(entry)
+}
+
+/// Read the password of `entry`, reassembling it if it was split into
+/// chunks, unless it has expired, see [purge].
+pub(crate) fn get_password(entry: &Entry) -> Result<String> {
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if entry.as_any().is::<crate::secret_service::Cred>() {
+    return get_secret(entry).and_then(|secret| {
+      String::from_utf8(secret).map_err(|err| Error::BadEncoding(err.into_bytes()))
+    });
+  }
+  purge(entry)?;
+  chunked::get_password(entry)
+}
+
+/// The number of seconds the credential of `entry` has left, or `None` if
+/// it never expires.
+///
+/// Returns a [NoEntry](Error::NoEntry) error if there is no credential.
+/// keyutils reports the remaining time in the largest whole unit, from
+/// seconds up to weeks, so it is rounded down to that unit.
+pub(crate) fn remaining(entry: &Entry) -> Result<Option<u32>> {
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = keyutils_cred(entry) {
+    return keyutils::remaining(&find_key(cred)?);
+  }
+  if !attributes::keeps_arbitrary(entry) {
+    return entry.get_credential().map(|_| None);
+  }
+  let mut attributes = entry.get_attributes()?;
+  let expires_at = expires_at(&attributes)?;
+  check(entry, &mut attributes)?;
+  Ok(
+    expires_at
+      .map(|expires_at| u32::try_from(expires_at.saturating_sub(now())).unwrap_or(u32::MAX)),
+  )
+}
+
+/// The [EXPIRES_AT] attribute among `attributes`, unless it is empty.
+fn expires_at(attributes: &HashMap<String, String>) -> Result<Option<u64>> {
+  match attributes.get(EXPIRES_AT).map(String::as_str) {
+    None | Some("") => Ok(None),
+    Some(expires_at) => expires_at.parse().map(Some).map_err(|_| {
+      Error::BadDataFormat(
+        expires_at.as_bytes().to_vec(),
+        format!("{EXPIRES_AT} is not a number of seconds").into(),
+      )
+    }),
+  }
+}
+
+fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(target_os = "linux")]
+fn keyutils_cred(entry: &Entry) -> Option<&linux_keyutils_keyring_store::Cred> {
+  entry.as_any().downcast_ref()
+}
+
+#[cfg(target_os = "linux")]
+mod keyutils {
+  use keyring_core::{Error, Result};
+  use linux_keyutils::Key;
+
+  /// Read the timeout of `key` from `/proc/keys`, the only place the kernel reports it.
+  pub(super) fn remaining(key: &Key) -> Result<Option<u32>> {
+    let serial = format!("{:08x}", key.get_id().as_raw_id());
+    let keys =
+      std::fs::read_to_string("/proc/keys").map_err(|err| Error::PlatformFailure(Box::new(err)))?;
+    let expiry = keys
+      .lines()
+      .map(|line| line.split_whitespace())
+      .find_map(|mut fields| {
+        (fields.next() == Some(serial.as_str()))
+          .then(|| fields.nth(2))
+          .flatten()
+      })
+      .ok_or(Error::NoEntry)?;
+    if expiry == "perm" {
+      return Ok(None);
+    }
+    if expiry == "expd" {
+      return Err(Error::NoEntry);
+    }
+    let (count, unit) = expiry.split_at(expiry.len() - 1);
+    let unit = match unit {
+      "s" => 1,
+      "m" => 60,
+      "h" => 60 * 60,
+      "d" => 60 * 60 * 24,
+      "w" => 60 * 60 * 24 * 7,
+      _ => 0,
+    };
+    count
+      .parse::<u32>()
+      .ok()
+      .filter(|_| unit > 0)
+      .map(|count| Some(count.saturating_mul(unit)))
+      .ok_or_else(|| {
+        Error::BadDataFormat(
+          expiry.as_bytes().to_vec(),
+          "unknown expiry in /proc/keys".into(),
+        )
+      })
+  }
+}
@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+
+use keyring_core::api::{CredentialPersistence, CredentialStoreApi};
+use keyring_core::attributes::parse_attributes;
+use keyring_core::{Entry, Error, Result};
+use linux_keyutils::{Key, KeyError, KeyRing, KeyRingIdentifier, KeySerialId, KeyType, LinkNode};
+use linux_keyutils_keyring_store::{Cred, Store as InnerStore};
+
+/// How many links are read from a keyring when searching it.
+const MAX_LINKS: usize = 1024;
+
+/// The store option that selects the keyring, see [Keyring].
+const KEYRING_OPTION: &str = "keyring";
+
+/// The largest payload of a `user` key, which credentials are written to.
+pub(crate) const USER_KEY_MAX_LEN: usize = 32767;
+/// The largest payload of a `big_key` key, which larger secrets are written to.
+pub(crate) const BIG_KEY_MAX_LEN: usize = 1024 * 1024 - 1;
+
+const KEYCTL_GET_KEYRING_ID: libc::c_long = 0;
+const KEYCTL_SEARCH: libc::c_long = 10;
+const KEYCTL_READ: libc::c_long = 11;
+const KEYCTL_GET_PERSISTENT: libc::c_long = 22;
+
+/// The keyring credentials are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyring {
+  /// The session keyring, with keys also linked to the persistent keyring.
+  Session,
+  /// The user keyring, shared by every process of the user until reboot.
+  User,
+  /// The per-user persistent keyring, which survives logout.
+  Persistent,
+  /// A keyring private to this process.
+  Process,
+}
+
+impl Keyring {
+  fn parse(name: &str) -> Result<Self> {
+    match name {
+      "session" => Ok(Keyring::Session),
+      "user" => Ok(Keyring::User),
+      "persistent" => Ok(Keyring::Persistent),
+      "process" => Ok(Keyring::Process),
+      _ => Err(Error::Invalid(
+        KEYRING_OPTION.to_owned(),
+        "must be one of session, user, persistent or process".to_owned(),
+      )),
+    }
+  }
+
+  /// Look up the keyring, creating it unless it is the session keyring.
+  fn open(&self) -> Result<KeyRing> {
+    match self {
+      Keyring::Session => KeyRing::from_special_id(KeyRingIdentifier::Session, false),
+      Keyring::User => possessed_user_keyring(),
+      // The persistent keyring is linked to the user keyring, so it is possessed through it.
+      Keyring::Persistent => {
+        possessed_user_keyring().and_then(|_| KeyRing::get_persistent(KeyRingIdentifier::User))
+      }
+      Keyring::Process => KeyRing::from_special_id(KeyRingIdentifier::Process, true),
+    }
+    .map_err(|err| Error::NoStorageAccess(err.into()))
+  }
+
+  /// The serial number of the keyring, which `linux_keyutils` keeps private.
+  fn serial(&self) -> Option<i32> {
+    let (operation, first, second) = match self {
+      Keyring::Session => (KEYCTL_GET_KEYRING_ID, KeyRingIdentifier::Session as _, 0),
+      Keyring::User => (KEYCTL_GET_KEYRING_ID, KeyRingIdentifier::User as _, 1),
+      Keyring::Process => (KEYCTL_GET_KEYRING_ID, KeyRingIdentifier::Process as _, 1),
+      // The persistent keyring of the calling user, linked to the user keyring.
+      Keyring::Persistent => (KEYCTL_GET_PERSISTENT, -1, KeyRingIdentifier::User as _),
+    };
+    let serial = unsafe {
+      libc::syscall(
+        libc::SYS_keyctl,
+        operation,
+        first as libc::c_long,
+        second as libc::c_long,
+      )
+    };
+    i32::try_from(serial).ok().filter(|serial| *serial > 0)
+  }
+}
+
+/// The user keyring, linked to the process keyring.
+///
+/// Keys are only readable by processes that possess them, i.e. reach them
+/// from their own keyrings, and the user keyring is not linked to every
+/// session, e.g. not to the one of a systemd service.
+fn possessed_user_keyring() -> std::result::Result<KeyRing, KeyError> {
+  let user = KeyRing::from_special_id(KeyRingIdentifier::User, true)?;
+  KeyRing::from_special_id(KeyRingIdentifier::Process, true)?.link_keyring(user)?;
+  Ok(user)
+}
+
+/// The keyutils store, extended with search and a choice of keyring.
+///
+/// Credentials are built by `linux_keyutils_keyring_store`, and moved to the
+/// keyring named by the `keyring` option unless it is the session keyring.
+/// Search lists the keys linked to that keyring, or to the session and
+/// persistent keyrings, and parses their descriptions back into service and
+/// user with the store delimiters.
+#[derive(Debug)]
+pub struct Store {
+  inner: Arc<InnerStore>,
+  keyring: Keyring,
+}
+
+impl Store {
+  pub fn new_with_configuration(config: &HashMap<&str, &str>) -> Result<Arc<Self>> {
+    let mut config = config.clone();
+    let keyring = match config.remove(KEYRING_OPTION) {
+      Some(name) => Keyring::parse(name)?,
+      None => Keyring::Session,
+    };
+    if keyring != Keyring::Session {
+      // Fail now rather than on the first write if the keyring is not available.
+      keyring.open()?;
+    }
+    Ok(Arc::new(Store {
+      inner: InnerStore::new_with_configuration(&config)?,
+      keyring,
+    }))
+  }
+
+  /// The serial number of the keyring credentials are written to.
+  pub fn keyring_serial(&self) -> Option<i32> {
+    self.keyring.serial()
+  }
+
+  /// Split a key description into service and user, if it was written by this store.
+  fn parse_description(&self, description: &str) -> Option<(String, String)> {
+    let [prefix, divider, suffix] = &self.inner.delimiters;
+    let rest = description
+      .strip_prefix(prefix.as_str())?
+      .strip_suffix(suffix.as_str())?;
+    // A service without dividers means the last one ends the user, otherwise
+    // users are assumed not to contain the divider.
+    let (user, service) = if self.inner.service_no_divider {
+      rest.rsplit_once(divider.as_str())?
+    } else {
+      rest.split_once(divider.as_str())?
+    };
+    Some((service.to_owned(), user.to_owned()))
+  }
+}
+
+pub(crate) fn keyutils_error(err: KeyError) -> Error {
+  match err {
+    KeyError::KeyDoesNotExist | KeyError::KeyRevoked | KeyError::KeyExpired => Error::NoEntry,
+    KeyError::AccessDenied => Error::NoStorageAccess(err.into()),
+    err => Error::PlatformFailure(err.into()),
+  }
+}
+
+/// Find the key of `cred`: its `user` key or, for large secrets, its `big_key`.
+pub(crate) fn find_key(cred: &Cred) -> Result<Key> {
+  match cred.session.search(&cred.description) {
+    Err(KeyError::KeyDoesNotExist) => find_big_key(cred)?.ok_or(Error::NoEntry),
+    result => result.map_err(keyutils_error),
+  }
+}
+
+/// Read the payload of `key`, which `Key::read_to_vec` caps at 64 KiB.
+pub(crate) fn read_key(key: &Key) -> Result<Vec<u8>> {
+  let id = key.get_id().as_raw_id() as libc::c_long;
+  loop {
+    let len = keyctl_result(unsafe {
+      libc::syscall(
+        libc::SYS_keyctl,
+        KEYCTL_READ,
+        id,
+        std::ptr::null_mut::<u8>(),
+        0,
+      )
+    })?;
+    let mut buffer = vec![0u8; len];
+    let read = keyctl_result(unsafe {
+      libc::syscall(libc::SYS_keyctl, KEYCTL_READ, id, buffer.as_mut_ptr(), len)
+    })?;
+    // The payload can be updated between the calls, then try again.
+    if read <= len {
+      buffer.truncate(read);
+      return Ok(buffer);
+    }
+  }
+}
+
+/// Write `secret` to a `big_key` for `cred` and remove its `user` key.
+///
+/// Returns `false` if the kernel was built without `big_key` support.
+pub(crate) fn set_big_key(cred: &Cred, secret: &[u8]) -> Result<bool> {
+  let serial = keyring_serial(cred).ok_or_else(|| {
+    Error::NotSupportedByStore("big keys need an entry of the active keyutils store".to_owned())
+  })?;
+  let description = c_description(cred)?;
+  let id = unsafe {
+    libc::syscall(
+      libc::SYS_add_key,
+      <&CStr>::from(KeyType::BigKey).as_ptr(),
+      description.as_ptr(),
+      secret.as_ptr(),
+      secret.len(),
+      serial as libc::c_long,
+    )
+  };
+  let id = match keyctl_result(id) {
+    Ok(id) => id,
+    Err(Error::PlatformFailure(err))
+      if matches!(
+        err.downcast_ref::<KeyError>(),
+        Some(KeyError::Unknown(libc::ENODEV) | KeyError::OperationNotSupported)
+      ) =>
+    {
+      return Ok(false);
+    }
+    Err(err) => return Err(err),
+  };
+  let key = Key::from_id(KeySerialId::new(id as i32));
+  if let Some(persistent) = cred.persistent {
+    persistent.link_key(key).map_err(keyutils_error)?;
+  }
+  // Reads look for the `user` key first, so it must not outlive the write.
+  match cred.session.search(&cred.description) {
+    Ok(key) => key.invalidate().map_err(keyutils_error)?,
+    Err(KeyError::KeyDoesNotExist) => {}
+    Err(err) => return Err(keyutils_error(err)),
+  }
+  Ok(true)
+}
+
+/// Remove the `big_key` of `cred`, after its secret was written to a `user` key.
+pub(crate) fn remove_big_key(cred: &Cred) -> Result<()> {
+  match find_big_key(cred)? {
+    Some(key) => key.invalidate().map_err(keyutils_error),
+    None => Ok(()),
+  }
+}
+
+fn find_big_key(cred: &Cred) -> Result<Option<Key>> {
+  let Some(serial) = keyring_serial(cred) else {
+    return Ok(None);
+  };
+  let description = c_description(cred)?;
+  let id = unsafe {
+    libc::syscall(
+      libc::SYS_keyctl,
+      KEYCTL_SEARCH,
+      serial as libc::c_long,
+      <&CStr>::from(KeyType::BigKey).as_ptr(),
+      description.as_ptr(),
+      0 as libc::c_long,
+    )
+  };
+  match keyctl_result(id) {
+    Ok(id) => Ok(Some(Key::from_id(KeySerialId::new(id as i32)))),
+    Err(Error::NoEntry) => Ok(None),
+    Err(err) => Err(err),
+  }
+}
+
+/// The serial number of the keyring `cred` writes to, which `linux_keyutils`
+/// keeps private, if the active store built it.
+fn keyring_serial(cred: &Cred) -> Option<i32> {
+  let store = keyring_core::get_default_store()?;
+  let store = store.as_any().downcast_ref::<Store>()?;
+  if store.keyring.open().ok()? != cred.session {
+    return None;
+  }
+  store.keyring.serial()
+}
+
+fn c_description(cred: &Cred) -> Result<CString> {
+  CString::new(cred.description.as_str()).map_err(|_| {
+    Error::Invalid(
+      "description".to_owned(),
+      "cannot contain a NUL character".to_owned(),
+    )
+  })
+}
+
+/// Turn the result of a raw keyutils syscall into a length or serial number.
+fn keyctl_result(result: libc::c_long) -> Result<usize> {
+  usize::try_from(result).map_err(|_| keyutils_error(KeyError::from_errno()))
+}
+
+impl CredentialStoreApi for Store {
+  fn vendor(&self) -> String {
+    self.inner.vendor()
+  }
+
+  fn id(&self) -> String {
+    self.inner.id()
+  }
+
+  fn build(
+    &self,
+    service: &str,
+    user: &str,
+    modifiers: Option<&HashMap<&str, &str>>,
+  ) -> Result<Entry> {
+    let entry = self.inner.build(service, user, modifiers)?;
+    if self.keyring == Keyring::Session {
+      return Ok(entry);
+    }
+    let cred = entry
+      .as_any()
+      .downcast_ref::<Cred>()
+      .expect("The keyutils store builds keyutils credentials, please report a bug");
+    Ok(Entry::new_with_credential(Arc::new(Cred {
+      session: self.keyring.open()?,
+      persistent: None,
+      ..cred.clone()
+    })))
+  }
+
+  /// The spec may contain `service` and `user`, which must match exactly.
+  fn search(&self, spec: &HashMap<&str, &str>) -> Result<Vec<Entry>> {
+    let spec = parse_attributes(&["service", "user"], Some(spec))?;
+    let persistent = match self.keyring {
+      Keyring::Session => KeyRing::get_persistent(KeyRingIdentifier::Session).ok(),
+      _ => None,
+    };
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    for ring in std::iter::once(self.keyring.open()?).chain(persistent) {
+      for node in ring.get_links(MAX_LINKS).map_err(keyutils_error)?.iter() {
+        let LinkNode::Key(key) = node else {
+          continue;
+        };
+        if !seen.insert(key.get_id().0) {
+          continue;
+        }
+        // Keys can be revoked or expire while we iterate.
+        let Ok(metadata) = key.metadata() else {
+          continue;
+        };
+        if !matches!(metadata.get_type(), KeyType::User | KeyType::BigKey) {
+          continue;
+        }
+        let Some((service, user)) = self.parse_description(metadata.get_description()) else {
+          continue;
+        };
+        if spec.get("service").is_some_and(|s| *s != service)
+          || spec.get("user").is_some_and(|u| *u != user)
+        {
+          continue;
+        }
+        results.push(self.build(&service, &user, None)?);
+      }
+    }
+    Ok(results)
+  }
+
+  fn as_any(&self) -> &dyn std::any::Any {
+    self
+  }
+
+  fn persistence(&self) -> CredentialPersistence {
+    match self.keyring {
+      Keyring::Process => CredentialPersistence::ProcessOnly,
+      _ => self.inner.persistence(),
+    }
+  }
+
+  fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(self, f)
+  }
+}
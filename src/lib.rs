@@ -1,7 +1,29 @@
 #![deny(clippy::all)]
 
+pub mod ambiguity;
 pub mod async_entry;
+pub mod batch;
+pub mod collections;
 pub mod entry;
+pub mod error;
+pub mod exists;
+pub mod expiry;
+pub mod mock_store;
+pub mod permissions;
+pub mod search;
+pub mod specifier;
+pub mod store;
+pub mod url;
 
+mod attributes;
+mod chunked;
+mod conditional;
+mod worker;
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+#[cfg(target_os = "linux")]
+mod keyutils_store;
 #[cfg(target_os = "linux")]
 mod linux_credential_builder;
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+mod secret_service;
@@ -1,28 +1,49 @@
-use dbus_secret_service_keyring_store::Store as SecretServiceStore;
 use keyring_core::{CredentialStore, Result};
-use linux_keyutils_keyring_store::Store as KeyutilsStore;
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::keyutils_store::Store as KeyutilsStore;
+use crate::secret_service::Store as SecretServiceStore;
+use crate::store::Backend;
+
 /// A custom builder that falls back to keyutils if secret-service is not available.
 pub struct LinuxCredentialBuilder {
   store: Arc<CredentialStore>,
+  backend: Backend,
+  fallback_reason: Option<String>,
 }
 
 impl LinuxCredentialBuilder {
   pub fn new() -> Result<Self> {
     // Try to create secret service store, fallback to keyutils if it fails
-    let store: Arc<CredentialStore> =
+    let (store, backend, fallback_reason): (Arc<CredentialStore>, Backend, _) =
       match SecretServiceStore::new_with_configuration(&HashMap::new()) {
-        Ok(ss_store) => ss_store,
-        Err(_) => KeyutilsStore::new_with_configuration(&HashMap::new())?,
+        Ok(ss_store) => (ss_store, Backend::SecretService, None),
+        Err(err) => (
+          KeyutilsStore::new_with_configuration(&HashMap::new())?,
+          Backend::Keyutils,
+          Some(format!("Secret Service is not available: {err}")),
+        ),
       };
 
-    Ok(Self { store })
+    Ok(Self {
+      store,
+      backend,
+      fallback_reason,
+    })
   }
 
   pub fn get_store(&self) -> Arc<CredentialStore> {
     self.store.clone()
   }
+
+  pub fn backend(&self) -> Backend {
+    self.backend
+  }
+
+  /// Why the Secret Service store could not be used, if the builder fell back to keyutils.
+  pub fn fallback_reason(&self) -> Option<&str> {
+    self.fallback_reason.as_deref()
+  }
 }
@@ -0,0 +1,664 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use keyring_core::api::{CredentialApi, CredentialStoreApi};
+use keyring_core::attributes::parse_attributes;
+use keyring_core::{Credential, CredentialPersistence, Entry, Error, Result};
+use napi::bindgen_prelude::Uint8Array;
+use napi_derive::napi;
+
+use crate::error::{ErrorCode, to_napi_error};
+
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Store operations that can be made to fail or wait with `MockStore.failNext` and `MockStore.delayNext`.
+pub enum MockOperation {
+  /// Reading a password or secret.
+  Get,
+  /// Writing a password or secret.
+  Set,
+  /// Deleting a credential.
+  Delete,
+  /// Searching for credentials.
+  Search,
+  /// Unlocking a locked credential.
+  Unlock,
+}
+
+#[napi(object)]
+/// A credential held by the mock store.
+pub struct MockCredential {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+  pub secret: Uint8Array,
+  pub attributes: Option<HashMap<String, String>>,
+  /// When the credential was created, in milliseconds since the Unix epoch.
+  pub created: Option<i64>,
+  /// When the secret was last written, in milliseconds since the Unix epoch.
+  pub modified: Option<i64>,
+  /// A locked credential can only be used after it is unlocked.
+  pub locked: Option<bool>,
+}
+
+/// A credential as the store keeps it, see [MockCredential].
+#[derive(Clone)]
+pub(crate) struct Stored {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+  pub secret: Vec<u8>,
+  pub attributes: Option<HashMap<String, String>>,
+  pub created: Option<i64>,
+  pub modified: Option<i64>,
+  pub locked: Option<bool>,
+}
+
+impl From<Stored> for MockCredential {
+  fn from(stored: Stored) -> Self {
+    MockCredential {
+      service: stored.service,
+      user: stored.user,
+      target: stored.target,
+      secret: stored.secret.into(),
+      attributes: stored.attributes,
+      created: stored.created,
+      modified: stored.modified,
+      locked: stored.locked,
+    }
+  }
+}
+
+impl From<MockCredential> for Stored {
+  fn from(credential: MockCredential) -> Self {
+    Stored {
+      service: credential.service,
+      user: credential.user,
+      target: credential.target,
+      secret: credential.secret.to_vec(),
+      attributes: credential.attributes,
+      created: credential.created,
+      modified: credential.modified,
+      locked: credential.locked,
+    }
+  }
+}
+
+#[napi(object)]
+/// Restricts an injected failure or delay to matching credentials.
+pub struct MockFailureOptions {
+  pub service: Option<String>,
+  pub user: Option<String>,
+  /// How many matching calls are affected, at least 1. Defaults to 1.
+  pub times: Option<u32>,
+}
+
+enum Effect {
+  Fail(ErrorCode),
+  Delay(Duration),
+}
+
+struct Failure {
+  operation: MockOperation,
+  effect: Effect,
+  service: Option<String>,
+  user: Option<String>,
+  times: u32,
+}
+
+#[derive(Default)]
+struct State {
+  credentials: Vec<Stored>,
+  failures: Vec<Failure>,
+  /// Longer secrets are rejected with a `TooLong` error, like platform stores do.
+  max_secret_len: Option<usize>,
+}
+
+impl State {
+  fn positions(
+    &self,
+    service: &str,
+    user: &str,
+    target: Option<&str>,
+  ) -> impl Iterator<Item = usize> {
+    self
+      .credentials
+      .iter()
+      .enumerate()
+      .filter(move |(_, c)| c.service == service && c.user == user && c.target.as_deref() == target)
+      .map(|(index, _)| index)
+  }
+
+  /// Consume the first effect programmed for this call that `pick` accepts.
+  fn take<T>(
+    &mut self,
+    operation: MockOperation,
+    service: Option<&str>,
+    user: Option<&str>,
+    pick: impl Fn(&Effect) -> Option<T>,
+  ) -> Option<T> {
+    let (index, picked) = self.failures.iter().enumerate().find_map(|(index, f)| {
+      (f.operation == operation
+        && f.service.as_deref().is_none_or(|s| Some(s) == service)
+        && f.user.as_deref().is_none_or(|u| Some(u) == user))
+      .then(|| pick(&f.effect).map(|picked| (index, picked)))
+      .flatten()
+    })?;
+    let failure = &mut self.failures[index];
+    failure.times -= 1;
+    if failure.times == 0 {
+      self.failures.remove(index);
+    }
+    Some(picked)
+  }
+
+  /// Consume the first failure programmed for this call, if any.
+  fn take_failure(
+    &mut self,
+    state: &Arc<Mutex<State>>,
+    operation: MockOperation,
+    service: Option<&str>,
+    user: Option<&str>,
+  ) -> Result<()> {
+    let Some(code) = self.take(operation, service, user, |effect| match effect {
+      Effect::Fail(code) => Some(*code),
+      Effect::Delay(_) => None,
+    }) else {
+      return Ok(());
+    };
+    let injected = || -> Box<dyn std::error::Error + Send + Sync> {
+      Box::new(std::io::Error::other("failure injected by the mock store"))
+    };
+    Err(match code {
+      ErrorCode::NoEntry => Error::NoEntry,
+      ErrorCode::Ambiguous => Error::Ambiguous(
+        self
+          .credentials
+          .iter()
+          .filter(|c| service.is_none_or(|s| s == c.service) && user.is_none_or(|u| u == c.user))
+          .map(|c| Cred::entry(state, &c.service, &c.user, c.target.as_deref()))
+          .collect(),
+      ),
+      ErrorCode::NoStorageAccess => Error::NoStorageAccess(injected()),
+      ErrorCode::PlatformFailure => Error::PlatformFailure(injected()),
+      ErrorCode::BadEncoding => Error::BadEncoding(Vec::new()),
+      ErrorCode::TooLong => Error::TooLong("secret".to_owned(), 0),
+      ErrorCode::Invalid => Error::Invalid("mock".to_owned(), "injected failure".to_owned()),
+      ErrorCode::NotSupported => Error::NotSupportedByStore("injected failure".to_owned()),
+      ErrorCode::Timeout | ErrorCode::Aborted => unreachable!("rejected by failNext"),
+    })
+  }
+}
+
+/// Lock the state for `operation`, after the delay and failure programmed for it.
+fn begin<'a>(
+  state: &'a Arc<Mutex<State>>,
+  operation: MockOperation,
+  service: Option<&str>,
+  user: Option<&str>,
+) -> Result<MutexGuard<'a, State>> {
+  let mut guard = lock(state);
+  let delay = guard.take(operation, service, user, |effect| match effect {
+    Effect::Delay(delay) => Some(*delay),
+    Effect::Fail(_) => None,
+  });
+  if let Some(delay) = delay {
+    // The store stays usable while this call waits.
+    drop(guard);
+    std::thread::sleep(delay);
+    guard = lock(state);
+  }
+  guard.take_failure(state, operation, service, user)?;
+  Ok(guard)
+}
+
+fn now_millis() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_millis() as i64)
+    .unwrap_or_default()
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+  state
+    .lock()
+    .expect("Poisoned mock store lock, please report a bug")
+}
+
+/// A credential in the mock store, identified by service, user and target.
+pub struct Cred {
+  state: Arc<Mutex<State>>,
+  service: String,
+  user: String,
+  target: Option<String>,
+  /// Which of several credentials with this identity the entry stands for,
+  /// as handed out by an `Ambiguous` error.
+  duplicate: Option<usize>,
+}
+
+impl std::fmt::Debug for Cred {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Cred")
+      .field("service", &self.service)
+      .field("user", &self.user)
+      .field("target", &self.target)
+      .field("duplicate", &self.duplicate)
+      .finish()
+  }
+}
+
+impl std::fmt::Debug for State {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("State")
+      .field("credentials", &self.credentials.len())
+      .field("failures", &self.failures.len())
+      .finish()
+  }
+}
+
+impl Cred {
+  fn entry(state: &Arc<Mutex<State>>, service: &str, user: &str, target: Option<&str>) -> Entry {
+    Entry::new_with_credential(Arc::new(Cred {
+      state: state.clone(),
+      service: service.to_owned(),
+      user: user.to_owned(),
+      target: target.map(ToOwned::to_owned),
+      duplicate: None,
+    }))
+  }
+
+  /// The index of the stored credential, if there is one.
+  ///
+  /// Restored snapshots can hold several credentials with the same identity,
+  /// which are reported as an `Ambiguous` error unless this is one of its handles.
+  fn find(&self, state: &State) -> Result<Option<usize>> {
+    let positions: Vec<_> = state
+      .positions(&self.service, &self.user, self.target.as_deref())
+      .collect();
+    if let Some(duplicate) = self.duplicate {
+      return Ok(positions.get(duplicate).copied());
+    }
+    if positions.len() > 1 {
+      return Err(Error::Ambiguous(
+        (0..positions.len())
+          .map(|duplicate| {
+            Entry::new_with_credential(Arc::new(Cred {
+              state: self.state.clone(),
+              service: self.service.clone(),
+              user: self.user.clone(),
+              target: self.target.clone(),
+              duplicate: Some(duplicate),
+            }))
+          })
+          .collect(),
+      ));
+    }
+    Ok(positions.first().copied())
+  }
+
+  /// The target the credential is written to, if any.
+  pub(crate) fn target(&self) -> Option<&str> {
+    self.target.as_deref()
+  }
+
+  /// A copy of the stored credential, without consuming injected failures.
+  pub(crate) fn stored(&self) -> Result<Stored> {
+    let state = lock(&self.state);
+    self
+      .find(&state)?
+      .map(|index| state.credentials[index].clone())
+      .ok_or(Error::NoEntry)
+  }
+
+  fn with_state<T>(
+    &self,
+    operation: MockOperation,
+    f: impl FnOnce(&mut State, Option<usize>) -> Result<T>,
+  ) -> Result<T> {
+    let mut state = begin(
+      &self.state,
+      operation,
+      Some(&self.service),
+      Some(&self.user),
+    )?;
+    let index = self.find(&state)?;
+    if index.is_some_and(|index| state.credentials[index].locked == Some(true)) {
+      return Err(Error::NoStorageAccess(Box::new(std::io::Error::other(
+        "The credential is locked",
+      ))));
+    }
+    f(&mut state, index)
+  }
+
+  /// Unlock the stored credential, as if the user accepted an unlock prompt.
+  pub(crate) fn unlock(&self) -> Result<()> {
+    let mut state = begin(
+      &self.state,
+      MockOperation::Unlock,
+      Some(&self.service),
+      Some(&self.user),
+    )?;
+    let index = self.find(&state)?.ok_or(Error::NoEntry)?;
+    state.credentials[index].locked = None;
+    Ok(())
+  }
+}
+
+impl CredentialApi for Cred {
+  fn set_secret(&self, secret: &[u8]) -> Result<()> {
+    self.with_state(MockOperation::Set, |state, index| {
+      if let Some(max) = state.max_secret_len.filter(|max| secret.len() > *max) {
+        return Err(Error::TooLong("secret".to_owned(), max as u32));
+      }
+      let now = now_millis();
+      match index {
+        Some(index) => {
+          let credential = &mut state.credentials[index];
+          credential.secret = secret.to_vec();
+          credential.modified = Some(now);
+        }
+        None => state.credentials.push(Stored {
+          service: self.service.clone(),
+          user: self.user.clone(),
+          target: self.target.clone(),
+          secret: secret.to_vec(),
+          attributes: None,
+          created: Some(now),
+          modified: Some(now),
+          locked: None,
+        }),
+      }
+      Ok(())
+    })
+  }
+
+  fn get_secret(&self) -> Result<Vec<u8>> {
+    self.with_state(MockOperation::Get, |state, index| {
+      index
+        .map(|index| state.credentials[index].secret.clone())
+        .ok_or(Error::NoEntry)
+    })
+  }
+
+  fn get_attributes(&self) -> Result<HashMap<String, String>> {
+    self.with_state(MockOperation::Get, |state, index| {
+      index
+        .map(|index| {
+          state.credentials[index]
+            .attributes
+            .clone()
+            .unwrap_or_default()
+        })
+        .ok_or(Error::NoEntry)
+    })
+  }
+
+  fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+    if let Some(key) = attributes
+      .keys()
+      .find(|k| ["service", "user", "target"].contains(k))
+    {
+      return Err(Error::Invalid(
+        key.to_string(),
+        "cannot be updated".to_owned(),
+      ));
+    }
+    self.with_state(MockOperation::Set, |state, index| {
+      let index = index.ok_or(Error::NoEntry)?;
+      let existing = state.credentials[index]
+        .attributes
+        .get_or_insert_with(HashMap::new);
+      for (key, value) in attributes {
+        existing.insert(key.to_string(), value.to_string());
+      }
+      Ok(())
+    })
+  }
+
+  fn delete_credential(&self) -> Result<()> {
+    self.with_state(MockOperation::Delete, |state, index| {
+      let index = index.ok_or(Error::NoEntry)?;
+      state.credentials.remove(index);
+      Ok(())
+    })
+  }
+
+  fn get_credential(&self) -> Result<Option<Arc<Credential>>> {
+    self.with_state(MockOperation::Get, |_, index| {
+      index.map(|_| None).ok_or(Error::NoEntry)
+    })
+  }
+
+  fn get_specifiers(&self) -> Option<(String, String)> {
+    Some((self.service.clone(), self.user.clone()))
+  }
+
+  fn as_any(&self) -> &dyn std::any::Any {
+    self
+  }
+
+  fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(self, f)
+  }
+}
+
+/// An in-process credential store, used for the `mock` backend.
+///
+/// Unlike the `keyring_core` mock, it supports targets, search, attributes
+/// and failures injected from JS.
+#[derive(Debug)]
+pub struct Store {
+  id: String,
+  state: Arc<Mutex<State>>,
+}
+
+impl Store {
+  pub fn new() -> Arc<Self> {
+    let elapsed = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default();
+    Arc::new(Store {
+      id: format!(
+        "Crate version {}, Instantiated at {}",
+        env!("CARGO_PKG_VERSION"),
+        elapsed.as_secs_f64()
+      ),
+      state: Arc::new(Mutex::new(State::default())),
+    })
+  }
+}
+
+impl CredentialStoreApi for Store {
+  fn vendor(&self) -> String {
+    "Mock store, https://github.com/Brooooooklyn/keyring-node".to_owned()
+  }
+
+  fn id(&self) -> String {
+    self.id.clone()
+  }
+
+  fn build(
+    &self,
+    service: &str,
+    user: &str,
+    modifiers: Option<&HashMap<&str, &str>>,
+  ) -> Result<Entry> {
+    let mods = parse_attributes(&["+target"], modifiers)?;
+    Ok(Cred::entry(
+      &self.state,
+      service,
+      user,
+      mods.get("target").map(String::as_str),
+    ))
+  }
+
+  fn search(&self, spec: &HashMap<&str, &str>) -> Result<Vec<Entry>> {
+    let state = begin(
+      &self.state,
+      MockOperation::Search,
+      spec.get("service").copied(),
+      spec.get("user").copied(),
+    )?;
+    Ok(
+      state
+        .credentials
+        .iter()
+        .filter(|c| {
+          spec.iter().all(|(key, value)| match *key {
+            "service" => c.service == *value,
+            "user" => c.user == *value,
+            "target" => c.target.as_deref() == Some(*value),
+            key => c
+              .attributes
+              .as_ref()
+              .and_then(|attrs| attrs.get(key))
+              .is_some_and(|v| v == value),
+          })
+        })
+        .map(|c| Cred::entry(&self.state, &c.service, &c.user, c.target.as_deref()))
+        .collect(),
+    )
+  }
+
+  fn as_any(&self) -> &dyn std::any::Any {
+    self
+  }
+
+  fn persistence(&self) -> CredentialPersistence {
+    CredentialPersistence::ProcessOnly
+  }
+
+  fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(self, f)
+  }
+}
+
+#[napi]
+/// Inspect and control the mock store, see `getMockStore`.
+pub struct MockStore {
+  state: Arc<Mutex<State>>,
+}
+
+#[napi]
+/// Get a handle to the active mock store.
+///
+/// Throws a `NotSupported` error unless `configureStore({ backend: 'mock' })` was called.
+pub fn get_mock_store() -> napi::Result<MockStore, ErrorCode> {
+  keyring_core::get_default_store()
+    .and_then(|store| {
+      store
+        .as_any()
+        .downcast_ref::<Store>()
+        .map(|store| MockStore {
+          state: store.state.clone(),
+        })
+    })
+    .ok_or_else(|| {
+      to_napi_error(Error::NotSupportedByStore(
+        "The active store is not the mock store".to_owned(),
+      ))
+    })
+}
+
+#[napi]
+impl MockStore {
+  #[napi]
+  /// List the credentials currently held by the store.
+  pub fn credentials(&self) -> Vec<MockCredential> {
+    lock(&self.state)
+      .credentials
+      .iter()
+      .cloned()
+      .map(MockCredential::from)
+      .collect()
+  }
+
+  #[napi]
+  /// Take a copy of the store contents that can be passed to `restore`.
+  pub fn snapshot(&self) -> Vec<MockCredential> {
+    self.credentials()
+  }
+
+  #[napi]
+  /// Replace the store contents with a snapshot.
+  pub fn restore(&self, snapshot: Vec<MockCredential>) {
+    lock(&self.state).credentials = snapshot.into_iter().map(Stored::from).collect();
+  }
+
+  #[napi]
+  /// Remove all credentials and pending failures and delays.
+  pub fn clear(&self) {
+    let mut state = lock(&self.state);
+    state.credentials.clear();
+    state.failures.clear();
+  }
+
+  #[napi]
+  /// Reject secrets longer than `length` bytes with a `TooLong` error, as
+  /// platform stores with a size limit do, or accept any length if `null`.
+  pub fn set_max_secret_length(&self, length: Option<u32>) {
+    lock(&self.state).max_secret_len = length.map(|length| length as usize);
+  }
+
+  #[napi]
+  /// Make the next matching `operation` fail with an error of the given `code`.
+  ///
+  /// `Timeout` and `Aborted` are not store errors and cannot be injected.
+  pub fn fail_next(
+    &self,
+    operation: MockOperation,
+    code: ErrorCode,
+    options: Option<MockFailureOptions>,
+  ) -> napi::Result<(), ErrorCode> {
+    if matches!(code, ErrorCode::Timeout | ErrorCode::Aborted) {
+      return Err(to_napi_error(Error::Invalid(
+        "code".to_owned(),
+        format!("{} cannot be injected", code.as_ref()),
+      )));
+    }
+    self.program(operation, Effect::Fail(code), options)
+  }
+
+  #[napi]
+  /// Make the next matching `operation` wait `delayMs` milliseconds before it runs.
+  pub fn delay_next(
+    &self,
+    operation: MockOperation,
+    delay_ms: u32,
+    options: Option<MockFailureOptions>,
+  ) -> napi::Result<(), ErrorCode> {
+    self.program(
+      operation,
+      Effect::Delay(Duration::from_millis(delay_ms.into())),
+      options,
+    )
+  }
+
+  fn program(
+    &self,
+    operation: MockOperation,
+    effect: Effect,
+    options: Option<MockFailureOptions>,
+  ) -> napi::Result<(), ErrorCode> {
+    let options = options.unwrap_or(MockFailureOptions {
+      service: None,
+      user: None,
+      times: None,
+    });
+    if options.times == Some(0) {
+      return Err(to_napi_error(Error::Invalid(
+        "times".to_owned(),
+        "must be at least 1".to_owned(),
+      )));
+    }
+    lock(&self.state).failures.push(Failure {
+      operation,
+      effect,
+      service: options.service,
+      user: options.user,
+      times: options.times.unwrap_or(1),
+    });
+    Ok(())
+  }
+}
@@ -0,0 +1,187 @@
+use keyring_core::{Entry, Error, Result};
+use napi_derive::napi;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What a class of processes may do with a keyutils key, see `KeyPermissions`.
+pub enum KeyPermission {
+  /// Read the type, description and permissions of the key.
+  View,
+  /// Read the secret.
+  Read,
+  /// Update the secret.
+  Write,
+  /// Find the key by its description, and delete it.
+  Search,
+  /// Link the key to a keyring.
+  Link,
+  /// Change the timeout and the permissions of the key.
+  Setattr,
+}
+
+#[napi(object)]
+/// The permissions of a keyutils key, for each class of processes.
+///
+/// A process gets the permissions of every class it belongs to. Classes left
+/// out keep their permissions when they are set.
+pub struct KeyPermissions {
+  /// Processes that possess the key, i.e. reach it from their own keyrings.
+  pub possessor: Option<Vec<KeyPermission>>,
+  /// Processes of the user that owns the key.
+  pub user: Option<Vec<KeyPermission>>,
+  /// Processes of the group that owns the key.
+  pub group: Option<Vec<KeyPermission>>,
+  /// Every other process.
+  pub other: Option<Vec<KeyPermission>>,
+}
+
+#[napi(object)]
+/// Options of `Entry.shareWithChildren`.
+pub struct ShareOptions {
+  /// The permissions to give the key once it is shared, e.g. only `view`,
+  /// `read` and `search` for possessors so children cannot update it.
+  pub permissions: Option<KeyPermissions>,
+}
+
+/// The permissions of the key of `entry`.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+pub(crate) fn get(entry: &Entry) -> Result<KeyPermissions> {
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = keyutils::cred(entry) {
+    return keyutils::get(cred);
+  }
+  Err(not_supported())
+}
+
+/// Set the permissions of the key of `entry`, and of its chunks.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+pub(crate) fn set(entry: &Entry, permissions: &KeyPermissions) -> Result<()> {
+  #[cfg(target_os = "linux")]
+  if keyutils::cred(entry).is_some() {
+    return keyutils::set(entry, permissions);
+  }
+  Err(not_supported())
+}
+
+/// Link the key of `entry`, and its chunks, to the session keyring, which
+/// child processes inherit, then give it `options.permissions` if any.
+///
+/// Returns the serial number of the key.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+pub(crate) fn share_with_children(entry: &Entry, options: Option<&ShareOptions>) -> Result<i32> {
+  #[cfg(target_os = "linux")]
+  if keyutils::cred(entry).is_some() {
+    return keyutils::share_with_children(entry, options);
+  }
+  Err(not_supported())
+}
+
+fn not_supported() -> Error {
+  Error::NotSupportedByStore("only keyutils keys have permissions".to_owned())
+}
+
+#[cfg(target_os = "linux")]
+mod keyutils {
+  use keyring_core::{Entry, Error, Result};
+  use linux_keyutils::{KeyRing, KeyRingIdentifier, Permission};
+  use linux_keyutils_keyring_store::Cred;
+
+  use super::{KeyPermission, KeyPermissions, ShareOptions};
+  use crate::chunked;
+  use crate::keyutils_store::{find_key, keyutils_error};
+
+  /// The bit of each permission in the mask of a class.
+  const ALL: [(KeyPermission, u8); 6] = [
+    (KeyPermission::View, Permission::VIEW.bits()),
+    (KeyPermission::Read, Permission::READ.bits()),
+    (KeyPermission::Write, Permission::WRITE.bits()),
+    (KeyPermission::Search, Permission::SEARCH.bits()),
+    (KeyPermission::Link, Permission::LINK.bits()),
+    (KeyPermission::Setattr, Permission::SETATTR.bits()),
+  ];
+
+  pub(super) fn cred(entry: &Entry) -> Option<&Cred> {
+    entry.as_any().downcast_ref()
+  }
+
+  pub(super) fn get(cred: &Cred) -> Result<KeyPermissions> {
+    let bits = find_key(cred)?
+      .metadata()
+      .map_err(keyutils_error)?
+      .get_perms()
+      .bits();
+    // Possessor, user, group and other masks, from the highest byte down.
+    let class = |shift: u32| {
+      let mask = (bits >> shift) as u8;
+      Some(
+        ALL
+          .iter()
+          .filter(|(_, bit)| mask & bit != 0)
+          .map(|(name, _)| *name)
+          .collect(),
+      )
+    };
+    Ok(KeyPermissions {
+      possessor: class(24),
+      user: class(16),
+      group: class(8),
+      other: class(0),
+    })
+  }
+
+  pub(super) fn set(entry: &Entry, permissions: &KeyPermissions) -> Result<()> {
+    // Chunks go first, so a failure leaves the key, and with it whatever
+    // `setattr` permission is needed to try again, untouched.
+    for chunk in chunked::chunks(entry)? {
+      if let Some(cred) = cred(&chunk) {
+        set_key(cred, permissions)?;
+      }
+    }
+    set_key(cred(entry).expect("Checked by the caller"), permissions)
+  }
+
+  pub(super) fn share_with_children(entry: &Entry, options: Option<&ShareOptions>) -> Result<i32> {
+    // Joins a new session keyring if the process has none yet, so children
+    // forked from now on inherit it.
+    let session = KeyRing::from_special_id(KeyRingIdentifier::Session, true)
+      .map_err(|err| Error::NoStorageAccess(err.into()))?;
+    let key = find_key(cred(entry).expect("Checked by the caller"))?;
+    for chunk in chunked::chunks(entry)? {
+      if let Some(cred) = cred(&chunk) {
+        session.link_key(find_key(cred)?).map_err(keyutils_error)?;
+      }
+    }
+    session.link_key(key).map_err(keyutils_error)?;
+    if let Some(permissions) = options.and_then(|options| options.permissions.as_ref()) {
+      set(entry, permissions)?;
+    }
+    Ok(key.get_id().as_raw_id())
+  }
+
+  fn set_key(cred: &Cred, permissions: &KeyPermissions) -> Result<()> {
+    let key = find_key(cred)?;
+    let mut perms = key.metadata().map_err(keyutils_error)?.get_perms();
+    if let Some(class) = &permissions.possessor {
+      perms.set_posessor_perms(mask(class));
+    }
+    if let Some(class) = &permissions.user {
+      perms.set_user_perms(mask(class));
+    }
+    if let Some(class) = &permissions.group {
+      perms.set_group_perms(mask(class));
+    }
+    if let Some(class) = &permissions.other {
+      perms.set_world_perms(mask(class));
+    }
+    key.set_perms(perms).map_err(keyutils_error)
+  }
+
+  fn mask(class: &[KeyPermission]) -> Permission {
+    Permission::from_bits_truncate(
+      ALL
+        .iter()
+        .filter(|(name, _)| class.contains(name))
+        .fold(0, |mask, (_, bit)| mask | bit),
+    )
+  }
+}
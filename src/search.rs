@@ -0,0 +1,813 @@
+use std::collections::HashMap;
+#[cfg(target_os = "windows")]
+use std::sync::Arc;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::async_entry::{AsyncEntry, AsyncOptions};
+use crate::chunked;
+use crate::entry::Entry;
+use crate::error::{ErrorCode, to_js_error, to_napi_error};
+use crate::expiry;
+use crate::store::{Backend, active_store, ensure_default_store};
+use crate::worker::WorkerTask;
+
+/// A backend-independent search, translated to the spec of the active store.
+#[derive(Default)]
+pub(crate) struct SearchQuery {
+  pub service: Option<String>,
+  pub user: Option<String>,
+  pub target: Option<String>,
+  pub attributes: HashMap<String, String>,
+}
+
+impl From<CredentialQuery> for SearchQuery {
+  fn from(query: CredentialQuery) -> Self {
+    SearchQuery {
+      service: query.service,
+      user: query.user,
+      target: query.target,
+      attributes: query.attributes.unwrap_or_default(),
+    }
+  }
+}
+
+/// A credential found by [search].
+pub struct Hit {
+  pub(crate) entry: keyring_core::Entry,
+  /// Metadata the store returned along with the entry.
+  metadata: Option<Metadata>,
+  /// The D-Bus object path of a Secret Service item.
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  path: Option<String>,
+}
+
+impl Hit {
+  fn new(entry: keyring_core::Entry) -> Self {
+    Hit {
+      entry,
+      metadata: None,
+      #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+      path: None,
+    }
+  }
+
+  /// The metadata of the credential, read from the store unless the search returned it.
+  pub(crate) fn describe(&mut self) -> keyring_core::Result<Metadata> {
+    match self.metadata.take() {
+      Some(metadata) => Ok(metadata),
+      None => describe(&self.entry),
+    }
+  }
+}
+
+/// Search the active store for credentials matching `query`.
+///
+/// Stores that don't record a target ignore `query.target`. Results are
+/// checked against the service and user of each entry, so stores with
+/// looser matching still return exact matches only. Attributes are queried
+/// natively where the store supports it, and checked on each result otherwise.
+///
+/// Nothing is unlocked: Secret Service items are searched directly and
+/// returned as entries that unlock the item once their secret is used.
+pub(crate) fn search(query: &SearchQuery) -> keyring_core::Result<Vec<Hit>> {
+  let (backend, store) = active_store()?;
+  let (service_key, user_key, target_key) = match backend {
+    Backend::SecretService => ("service", "username", Some("target")),
+    // The keychain and keyutils stores take only these two. The Credential
+    // Manager is searched by [windows_search] instead.
+    Backend::Keychain | Backend::Keyutils | Backend::Windows => ("service", "user", None),
+    Backend::Mock => ("service", "user", Some("target")),
+  };
+  let native_attributes = matches!(backend, Backend::SecretService | Backend::Mock);
+  let mut spec = HashMap::new();
+  if native_attributes {
+    for (key, value) in &query.attributes {
+      spec.insert(key.as_str(), value.as_str());
+    }
+  }
+  if let Some(service) = &query.service {
+    spec.insert(service_key, service.as_str());
+  }
+  if let Some(user) = &query.user {
+    spec.insert(user_key, user.as_str());
+  }
+  if let (Some(key), Some(target)) = (target_key, &query.target) {
+    spec.insert(key, target.as_str());
+  }
+  let hits: Vec<Hit> = match backend {
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    Backend::SecretService => crate::secret_service::search(&spec)?
+      .into_iter()
+      .map(|(entry, metadata, path)| Hit {
+        entry,
+        metadata: Some(metadata),
+        path: Some(path),
+      })
+      .collect(),
+    #[cfg(target_os = "windows")]
+    Backend::Windows => windows_search(query, &store)?,
+    _ => store.search(&spec)?.into_iter().map(Hit::new).collect(),
+  };
+  let mut results = Vec::new();
+  for mut hit in hits {
+    let Some((service, user)) = hit.entry.get_specifiers() else {
+      continue;
+    };
+    if query.service.as_ref().is_some_and(|s| *s != service)
+      || query.user.as_ref().is_some_and(|u| *u != user)
+      || chunked::is_chunk(&user)
+    {
+      continue;
+    }
+    if !native_attributes && !query.attributes.is_empty() {
+      let Ok(metadata) = hit.describe() else {
+        continue;
+      };
+      if !query
+        .attributes
+        .iter()
+        .all(|(key, value)| metadata.attributes.get(key) == Some(value))
+      {
+        continue;
+      }
+      hit.metadata = Some(metadata);
+    }
+    if native_attributes && let Ok(mut metadata) = hit.describe() {
+      // A read would purge an expired credential, so leave it out. It is
+      // only deleted by a read or write of the entry itself.
+      if let Ok(true) = expiry::take_expired(&mut metadata.attributes) {
+        continue;
+      }
+      chunked::hide(&mut metadata.attributes);
+      hit.metadata = Some(metadata);
+    }
+    results.push(hit);
+  }
+  Ok(results)
+}
+
+/// Search the Credential Manager, whose store only matches the target names
+/// of credentials against a `pattern`.
+///
+/// The store tells the user and service apart by splitting the target name
+/// at its last divider, which gets dotted services such as URLs wrong. So
+/// the user comes from the `username` it writes along with each credential,
+/// and the service from the rest of the target name. Credentials written
+/// with a target don't record their service, so they get the queried one.
+#[cfg(target_os = "windows")]
+fn windows_search(
+  query: &SearchQuery,
+  store: &Arc<keyring_core::CredentialStore>,
+) -> keyring_core::Result<Vec<Hit>> {
+  let default = [String::new(), ".".to_owned(), String::new()];
+  let [prefix, divider, suffix] = store
+    .as_any()
+    .downcast_ref::<windows_native_keyring_store::Store>()
+    .map_or(&default, |store| &store.delimiters);
+  let part = |value: Option<&String>| value.map_or_else(|| ".*".to_owned(), |value| escape(value));
+  let pattern = match &query.target {
+    Some(target) => format!("^{}$", escape(target)),
+    None => format!(
+      "^{}{}{}{}{}$",
+      escape(prefix),
+      part(query.user.as_ref()),
+      escape(divider),
+      part(query.service.as_ref()),
+      escape(suffix)
+    ),
+  };
+  let mut hits = Vec::new();
+  for entry in store.search(&HashMap::from([("pattern", pattern.as_str())]))? {
+    let Ok(attributes) = entry.get_attributes() else {
+      continue;
+    };
+    let (Some(user), Some(name)) = (attributes.get("username"), attributes.get("target_name"))
+    else {
+      continue;
+    };
+    let service = match &query.target {
+      Some(_) => query.service.clone(),
+      None => name
+        .strip_prefix(&format!("{prefix}{user}{divider}"))
+        .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+        .map(ToOwned::to_owned),
+    };
+    if let Some(service) = service {
+      hits.push(Hit::new(crate::specifier::build_entry(
+        &service,
+        user,
+        query.target.as_deref(),
+      )?));
+    }
+  }
+  Ok(hits)
+}
+
+/// Escape `text` for a regular expression, like `regex::escape`.
+#[cfg(target_os = "windows")]
+fn escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if r"\.+*?()|[]{}^$#&-~".contains(c) {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Unlock the credentials of `hits`, prompting the user at most once.
+#[cfg_attr(
+  not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")),
+  allow(unused_variables)
+)]
+fn unlock(hits: &[&Hit], prompt_timeout_seconds: Option<u32>) -> keyring_core::Result<()> {
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  {
+    let paths: Vec<&str> = hits.iter().filter_map(|hit| hit.path.as_deref()).collect();
+    if !paths.is_empty() {
+      crate::secret_service::unlock(&paths, prompt_timeout_seconds)?;
+    }
+  }
+  for hit in hits {
+    if let Some(cred) = hit.entry.as_any().downcast_ref::<crate::mock_store::Cred>() {
+      cred.unlock()?;
+    }
+  }
+  Ok(())
+}
+
+/// What a store records about a credential besides its secret.
+#[derive(Default)]
+pub(crate) struct Metadata {
+  pub target: Option<String>,
+  pub label: Option<String>,
+  pub attributes: HashMap<String, String>,
+  /// Milliseconds since the Unix epoch.
+  pub created: Option<i64>,
+  /// Milliseconds since the Unix epoch.
+  pub modified: Option<i64>,
+  pub locked: bool,
+}
+
+/// Read the metadata of an entry.
+pub(crate) fn describe(entry: &keyring_core::Entry) -> keyring_core::Result<Metadata> {
+  if let Some(cred) = entry.as_any().downcast_ref::<crate::mock_store::Cred>() {
+    let stored = cred.stored()?;
+    return Ok(Metadata {
+      target: stored.target,
+      attributes: stored.attributes.unwrap_or_default(),
+      created: stored.created,
+      modified: stored.modified,
+      locked: stored.locked == Some(true),
+      ..Default::default()
+    });
+  }
+  #[cfg(target_os = "linux")]
+  if let Some(cred) = entry
+    .as_any()
+    .downcast_ref::<linux_keyutils_keyring_store::Cred>()
+  {
+    return Ok(Metadata {
+      label: Some(cred.description.clone()),
+      ..Default::default()
+    });
+  }
+  // The handles of an `Ambiguous` error wrap the items themselves.
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Some(path) = entry
+    .as_any()
+    .downcast_ref::<crate::secret_service::Cred>()
+    .and_then(crate::secret_service::Cred::item_path)
+  {
+    return crate::secret_service::describe_path(&path);
+  }
+  let attributes = match entry.get_attributes() {
+    Err(keyring_core::Error::NotSupportedByStore(_)) => HashMap::new(),
+    attributes => attributes?,
+  };
+  Ok(Metadata {
+    attributes,
+    ..Default::default()
+  })
+}
+
+#[napi(object)]
+/// Which credentials to search for. Every given field must match.
+pub struct CredentialQuery {
+  pub service: Option<String>,
+  pub user: Option<String>,
+  /// Ignored by stores that don't record a target.
+  pub target: Option<String>,
+  /// Attributes the store keeps with the credential, see `Credential.attributes`.
+  pub attributes: Option<HashMap<String, String>>,
+}
+
+#[napi(object)]
+pub struct Credential {
+  /// The service the credential belongs to.
+  pub service: String,
+  pub account: String,
+  /// The target the credential was written to, e.g. the Secret Service collection.
+  pub target: Option<String>,
+  /// The label shown by credential managers, or the key description on keyutils.
+  pub label: Option<String>,
+  /// Every attribute the store keeps with the credential.
+  pub attributes: HashMap<String, String>,
+  /// When the credential was created, in milliseconds since the Unix epoch.
+  pub created: Option<i64>,
+  /// When the credential was last modified, in milliseconds since the Unix epoch.
+  pub modified: Option<i64>,
+  /// Whether the credential was locked when it was found.
+  pub locked: bool,
+  /// The raw secret, absent if it could not be read.
+  pub secret: Option<Uint8Array>,
+  /// The secret decoded as UTF-8, absent if it is not valid UTF-8.
+  pub password: Option<String>,
+  /// Why `secret` or `password` is missing.
+  pub error: Option<CredentialError>,
+}
+
+#[napi(object)]
+pub struct CredentialError {
+  pub code: ErrorCode,
+  pub message: String,
+}
+
+impl From<keyring_core::Error> for CredentialError {
+  fn from(err: keyring_core::Error) -> Self {
+    let err = to_napi_error(err);
+    CredentialError {
+      code: err.status,
+      message: err.reason,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct FindOptions {
+  /// Read the secret of every credential. Defaults to `true`.
+  ///
+  /// Reading secrets may prompt the user to unlock the store. Without them,
+  /// only identities and metadata are returned.
+  pub include_secrets: Option<bool>,
+  /// What to do with locked credentials. Defaults to `'report'`.
+  pub locked: Option<LockedItems>,
+  /// How long an unlock prompt waits for the user, in seconds. Defaults to no limit.
+  pub prompt_timeout_seconds: Option<u32>,
+}
+
+#[napi(object)]
+/// Options of `findCredentialsAsync`.
+pub struct AsyncFindOptions {
+  /// See `FindOptions.includeSecrets`.
+  pub include_secrets: Option<bool>,
+  /// See `FindOptions.locked`.
+  pub locked: Option<LockedItems>,
+  /// See `FindOptions.promptTimeoutSeconds`.
+  pub prompt_timeout_seconds: Option<u32>,
+  /// See `AsyncOptions.timeoutMs`.
+  pub timeout_ms: Option<u32>,
+}
+
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What `findCredentials` does with credentials that are locked.
+pub enum LockedItems {
+  /// Leave them out of the results.
+  Skip,
+  /// Return them with `locked: true` and without their secret.
+  Report,
+  /// Ask the user to unlock them, then read their secret.
+  Unlock,
+}
+
+#[derive(Clone, Copy)]
+struct FindSettings {
+  include_secrets: bool,
+  locked: LockedItems,
+  prompt_timeout_seconds: Option<u32>,
+}
+
+impl FindSettings {
+  const LIST: FindSettings = FindSettings {
+    include_secrets: false,
+    locked: LockedItems::Report,
+    prompt_timeout_seconds: None,
+  };
+}
+
+impl From<Option<FindOptions>> for FindSettings {
+  fn from(options: Option<FindOptions>) -> Self {
+    let options = options.unwrap_or(FindOptions {
+      include_secrets: None,
+      locked: None,
+      prompt_timeout_seconds: None,
+    });
+    FindSettings {
+      include_secrets: options.include_secrets.unwrap_or(true),
+      locked: options.locked.unwrap_or(LockedItems::Report),
+      prompt_timeout_seconds: options.prompt_timeout_seconds,
+    }
+  }
+}
+
+impl From<Option<AsyncFindOptions>> for FindSettings {
+  fn from(options: Option<AsyncFindOptions>) -> Self {
+    options
+      .map(|options| FindOptions {
+        include_secrets: options.include_secrets,
+        locked: options.locked,
+        prompt_timeout_seconds: options.prompt_timeout_seconds,
+      })
+      .into()
+  }
+}
+
+#[napi(object, object_from_js = false)]
+/// A credential found by `listAccounts`, with a handle to read its secret on demand.
+pub struct Account {
+  pub service: String,
+  pub account: String,
+  pub target: Option<String>,
+  pub label: Option<String>,
+  pub attributes: HashMap<String, String>,
+  pub created: Option<i64>,
+  pub modified: Option<i64>,
+  pub locked: bool,
+  /// Why the metadata could not be read.
+  pub error: Option<CredentialError>,
+  pub entry: Entry,
+}
+
+#[napi(object, object_from_js = false)]
+/// A credential found by `listAccountsAsync`, with a handle to read its secret on demand.
+pub struct AsyncAccount {
+  pub service: String,
+  pub account: String,
+  pub target: Option<String>,
+  pub label: Option<String>,
+  pub attributes: HashMap<String, String>,
+  pub created: Option<i64>,
+  pub modified: Option<i64>,
+  pub locked: bool,
+  /// Why the metadata could not be read.
+  pub error: Option<CredentialError>,
+  pub entry: AsyncEntry,
+}
+
+/// A search hit, converted to a [Credential] or an account on the JS thread.
+pub struct FoundCredential {
+  service: String,
+  account: String,
+  metadata: keyring_core::Result<Metadata>,
+  /// The secret, if it was requested.
+  secret: Option<keyring_core::Result<Vec<u8>>>,
+  entry: keyring_core::Entry,
+}
+
+impl From<FoundCredential> for Account {
+  fn from(found: FoundCredential) -> Self {
+    let (metadata, error) = split_metadata(found.metadata);
+    Account {
+      service: found.service,
+      account: found.account,
+      target: metadata.target,
+      label: metadata.label,
+      attributes: metadata.attributes,
+      created: metadata.created,
+      modified: metadata.modified,
+      locked: metadata.locked,
+      error,
+      entry: found.entry.into(),
+    }
+  }
+}
+
+impl From<FoundCredential> for AsyncAccount {
+  fn from(found: FoundCredential) -> Self {
+    let (metadata, error) = split_metadata(found.metadata);
+    AsyncAccount {
+      service: found.service,
+      account: found.account,
+      target: metadata.target,
+      label: metadata.label,
+      attributes: metadata.attributes,
+      created: metadata.created,
+      modified: metadata.modified,
+      locked: metadata.locked,
+      error,
+      entry: found.entry.into(),
+    }
+  }
+}
+
+fn split_metadata(metadata: keyring_core::Result<Metadata>) -> (Metadata, Option<CredentialError>) {
+  match metadata {
+    Ok(metadata) => (metadata, None),
+    Err(err) => (Metadata::default(), Some(CredentialError::from(err))),
+  }
+}
+
+impl From<FoundCredential> for Credential {
+  fn from(found: FoundCredential) -> Self {
+    let (metadata, metadata_error) = split_metadata(found.metadata);
+    let (password, error, secret) = match found.secret {
+      None => (None, None, None),
+      Some(Ok(secret)) => match std::str::from_utf8(&secret) {
+        Ok(password) => (Some(password.to_owned()), None, Some(secret)),
+        Err(err) => (
+          None,
+          Some(CredentialError {
+            code: ErrorCode::BadEncoding,
+            message: format!("The secret is not valid UTF-8: {err}"),
+          }),
+          Some(secret),
+        ),
+      },
+      Some(Err(err)) => (None, Some(CredentialError::from(err)), None),
+    };
+    Credential {
+      service: found.service,
+      account: found.account,
+      target: metadata.target,
+      label: metadata.label,
+      attributes: metadata.attributes,
+      created: metadata.created,
+      modified: metadata.modified,
+      locked: metadata.locked,
+      secret: secret.map(Uint8Array::from),
+      password,
+      error: error.or(metadata_error),
+    }
+  }
+}
+
+pub struct FindCredentials {
+  query: SearchQuery,
+  settings: FindSettings,
+  /// Failure to set up the store, reported when the task runs.
+  setup: Option<keyring_core::Error>,
+}
+
+#[napi]
+impl Task for FindCredentials {
+  type Output = keyring_core::Result<Vec<FoundCredential>>;
+  type JsValue = Vec<Credential>;
+
+  #[inline]
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(match self.setup.take() {
+      Some(err) => Err(err),
+      None => find_credentials_(&self.query, self.settings),
+    })
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output
+      .map(|found| found.into_iter().map(Credential::from).collect())
+      .map_err(|err| to_js_error(&env, err))
+  }
+}
+
+#[napi]
+/// find credentials by service name
+///
+/// The active credential store is searched, see `configureStore`. A credential
+/// whose secret cannot be read or decoded is returned with an `error` instead
+/// of failing the whole search.
+pub fn find_credentials(
+  env: Env,
+  service: String,
+  target: Option<String>,
+  options: Option<FindOptions>,
+) -> Result<Vec<Credential>, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  find_credentials_(
+    &SearchQuery {
+      service: Some(service),
+      target,
+      ..Default::default()
+    },
+    options.into(),
+  )
+  .map(|found| found.into_iter().map(Credential::from).collect())
+  .map_err(to_napi_error)
+}
+
+#[napi(ts_return_type = "Promise<Array<Credential>>")]
+/// find credentials by service name
+///
+/// The active credential store is searched, see `configureStore`. A credential
+/// whose secret cannot be read or decoded is returned with an `error` instead
+/// of failing the whole search.
+pub fn find_credentials_async(
+  env: Env,
+  service: String,
+  target: Option<String>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncFindOptions>,
+) -> WorkerTask<FindCredentials> {
+  let timeout_ms = options.as_ref().and_then(|options| options.timeout_ms);
+  WorkerTask::with_optional_signal(
+    FindCredentials {
+      query: SearchQuery {
+        service: Some(service),
+        target,
+        ..Default::default()
+      },
+      settings: options.into(),
+      setup: ensure_default_store(&env).err(),
+    },
+    signal,
+  )
+  .with_timeout(timeout_ms)
+}
+
+#[napi]
+/// List the accounts of a service without reading their secrets.
+///
+/// Each account comes with an entry to read its secret on demand.
+pub fn list_accounts(
+  env: Env,
+  service: String,
+  target: Option<String>,
+) -> Result<Vec<Account>, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  find_credentials_(
+    &SearchQuery {
+      service: Some(service),
+      target,
+      ..Default::default()
+    },
+    FindSettings::LIST,
+  )
+  .map(|found| found.into_iter().map(Account::from).collect())
+  .map_err(to_napi_error)
+}
+
+pub struct ListAccounts {
+  query: SearchQuery,
+  /// Failure to set up the store, reported when the task runs.
+  setup: Option<keyring_core::Error>,
+}
+
+#[napi]
+impl Task for ListAccounts {
+  type Output = keyring_core::Result<Vec<FoundCredential>>;
+  type JsValue = Vec<AsyncAccount>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(match self.setup.take() {
+      Some(err) => Err(err),
+      None => find_credentials_(&self.query, FindSettings::LIST),
+    })
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output
+      .map(|found| found.into_iter().map(AsyncAccount::from).collect())
+      .map_err(|err| to_js_error(&env, err))
+  }
+}
+
+#[napi(ts_return_type = "Promise<Array<AsyncAccount>>")]
+/// List the accounts of a service without reading their secrets.
+///
+/// Each account comes with an entry to read its secret on demand.
+pub fn list_accounts_async(
+  env: Env,
+  service: String,
+  target: Option<String>,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<ListAccounts> {
+  WorkerTask::with_optional_signal(
+    ListAccounts {
+      query: SearchQuery {
+        service: Some(service),
+        target,
+        ..Default::default()
+      },
+      setup: ensure_default_store(&env).err(),
+    },
+    signal,
+  )
+  .with_timeout(options.and_then(|options| options.timeout_ms))
+}
+
+fn find_credentials_(
+  query: &SearchQuery,
+  settings: FindSettings,
+) -> keyring_core::Result<Vec<FoundCredential>> {
+  let mut hits = Vec::new();
+  for mut hit in search(query)? {
+    let metadata = hit.describe();
+    let locked = metadata.as_ref().is_ok_and(|metadata| metadata.locked);
+    if locked && settings.locked == LockedItems::Skip {
+      continue;
+    }
+    hits.push((hit, metadata));
+  }
+  let is_locked = |metadata: &keyring_core::Result<Metadata>| {
+    metadata.as_ref().is_ok_and(|metadata| metadata.locked)
+  };
+  // Why locked credentials are still locked, if unlocking them failed.
+  let mut unlock_error = None;
+  if settings.include_secrets && settings.locked == LockedItems::Unlock {
+    let locked: Vec<&Hit> = hits
+      .iter()
+      .filter(|(_, metadata)| is_locked(metadata))
+      .map(|(hit, _)| hit)
+      .collect();
+    if !locked.is_empty() {
+      match unlock(&locked, settings.prompt_timeout_seconds) {
+        Ok(()) => {
+          for (_, metadata) in hits.iter_mut() {
+            if let Ok(metadata) = metadata {
+              metadata.locked = false;
+            }
+          }
+        }
+        Err(err) => unlock_error = Some(err.to_string()),
+      }
+    }
+  }
+  Ok(
+    hits
+      .into_iter()
+      .filter_map(|(hit, metadata)| {
+        let (service, account) = hit.entry.get_specifiers()?;
+        let secret = match (settings.include_secrets, is_locked(&metadata)) {
+          (false, _) => None,
+          // Reading the secret of a locked credential would prompt the user.
+          (true, true) => unlock_error
+            .clone()
+            .map(|err| Err(keyring_core::Error::NoStorageAccess(err.into()))),
+          (true, false) => Some(chunked::get_secret(&hit.entry)),
+        };
+        Some(FoundCredential {
+          service,
+          account,
+          metadata,
+          secret,
+          entry: hit.entry,
+        })
+      })
+      .collect(),
+  )
+}
+
+pub struct SearchCredentials {
+  query: SearchQuery,
+  /// Failure to set up the store, reported when the task runs.
+  setup: Option<keyring_core::Error>,
+}
+
+#[napi]
+impl Task for SearchCredentials {
+  type Output = keyring_core::Result<Vec<Hit>>;
+  type JsValue = Vec<AsyncEntry>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(match self.setup.take() {
+      Some(err) => Err(err),
+      None => search(&self.query),
+    })
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output
+      .map(|hits| hits.into_iter().map(|hit| hit.entry.into()).collect())
+      .map_err(|err| to_js_error(&env, err))
+  }
+}
+
+#[napi]
+/// Search the active store and return an entry for each matching credential.
+pub fn search_credentials(env: Env, query: CredentialQuery) -> Result<Vec<Entry>, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  search(&query.into())
+    .map(|hits| hits.into_iter().map(|hit| hit.entry.into()).collect())
+    .map_err(to_napi_error)
+}
+
+#[napi(ts_return_type = "Promise<Array<AsyncEntry>>")]
+/// Search the active store and resolve to an entry for each matching credential.
+pub fn search_credentials_async(
+  env: Env,
+  query: CredentialQuery,
+  signal: Option<AbortSignal>,
+  options: Option<AsyncOptions>,
+) -> WorkerTask<SearchCredentials> {
+  WorkerTask::with_optional_signal(
+    SearchCredentials {
+      query: query.into(),
+      setup: ensure_default_store(&env).err(),
+    },
+    signal,
+  )
+  .with_timeout(options.and_then(|options| options.timeout_ms))
+}
@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dbus_secret_service::{Collection, EncryptionType, Item, Path, SecretService};
+use keyring_core::api::{CredentialApi, CredentialStoreApi};
+use keyring_core::attributes::parse_attributes;
+use keyring_core::{Credential, Entry, Error, Result};
+
+use crate::collections::Collection as CollectionInfo;
+use crate::search::Metadata;
+
+/// D-Bus errors after which the connection is opened again.
+const CONNECTION_ERRORS: &[&str] = &[
+  "org.freedesktop.DBus.Error.Disconnected",
+  "org.freedesktop.DBus.Error.NoReply",
+  "org.freedesktop.DBus.Error.ServiceUnknown",
+  "org.freedesktop.DBus.Error.NameHasNoOwner",
+  "org.freedesktop.Secret.Error.NoSession",
+];
+
+/// The attributes the store finds an item by, which can't be updated.
+const SPECIFIER_ATTRIBUTES: [&str; 3] = ["service", "username", "target"];
+
+/// The content type of the secrets written by the store.
+const CONTENT_TYPE: &str = "application/octet-stream";
+
+/// The connection and encrypted session shared by every Secret Service call.
+///
+/// It is opened on first use and dropped when a call fails because the bus
+/// or the Secret Service went away, so the next call connects again. Calls
+/// that may prompt the user use a [prompt_connection] instead.
+static CONNECTION: Mutex<Option<SecretService>> = Mutex::new(None);
+
+/// The secret and the attributes of an item.
+type Contents = (Vec<u8>, HashMap<String, String>);
+
+/// Map a Secret Service error the way the store does.
+pub(crate) fn decode_error(err: dbus_secret_service::Error) -> Error {
+  use dbus_secret_service::Error as E;
+
+  match err {
+    E::Locked | E::NoResult | E::Prompt => Error::NoStorageAccess(Box::new(err)),
+    _ => Error::PlatformFailure(Box::new(err)),
+  }
+}
+
+/// Whether `err` means the connection is no longer usable.
+fn is_disconnected(err: &Error) -> bool {
+  let (Error::PlatformFailure(source) | Error::NoStorageAccess(source)) = err else {
+    return false;
+  };
+  matches!(
+    source.downcast_ref::<dbus_secret_service::Error>(),
+    Some(dbus_secret_service::Error::Dbus(err))
+      if err.name().is_some_and(|name| CONNECTION_ERRORS.contains(&name))
+  )
+}
+
+fn connect() -> Result<SecretService> {
+  SecretService::connect(EncryptionType::Dh).map_err(decode_error)
+}
+
+/// Run `op` with the shared connection, connecting again once if it was lost.
+///
+/// The connection is held for the whole call, so calls run one at a time,
+/// and `op` must not prompt the user, see [prompt_connection].
+fn with_connection<T>(op: impl Fn(&SecretService) -> Result<T>) -> Result<T> {
+  let mut connection = CONNECTION
+    .lock()
+    .expect("Poisoned connection lock, please report a bug");
+  let ss = match connection.take() {
+    Some(ss) => ss,
+    None => connect()?,
+  };
+  match op(connection.insert(ss)) {
+    Err(err) if is_disconnected(&err) => {
+      *connection = None;
+      op(connection.insert(connect()?))
+    }
+    result => result,
+  }
+}
+
+/// The Secret Service store, sharing one connection across the process.
+///
+/// Credentials only remember their service, user and modifiers, and are
+/// looked up on the current connection on every call, so entries keep
+/// working after the connection is reopened.
+#[derive(Debug)]
+pub struct Store {
+  id: String,
+}
+
+impl Store {
+  /// Create the store, connecting to the Secret Service unless already connected.
+  pub fn new_with_configuration(config: &HashMap<&str, &str>) -> Result<Arc<Self>> {
+    if !config.is_empty() {
+      return Err(Error::NotSupportedByStore(
+        "Secret Service configuration is not supported".to_owned(),
+      ));
+    }
+    with_connection(|_| Ok(()))?;
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default();
+    Ok(Arc::new(Store {
+      id: format!(
+        "Crate version {}, Instantiated at {}",
+        env!("CARGO_PKG_VERSION"),
+        now.as_secs_f64()
+      ),
+    }))
+  }
+}
+
+impl CredentialStoreApi for Store {
+  fn vendor(&self) -> String {
+    "Secret Service store, https://crates.io/crates/dbus-secret-service".to_owned()
+  }
+
+  fn id(&self) -> String {
+    self.id.clone()
+  }
+
+  fn build(
+    &self,
+    service: &str,
+    user: &str,
+    modifiers: Option<&HashMap<&str, &str>>,
+  ) -> Result<Entry> {
+    let mods = parse_attributes(&["target", "label"], modifiers)?;
+    for key in ["target", "label"] {
+      if mods.get(key).is_some_and(|value| value.is_empty()) {
+        return Err(Error::Invalid(key.to_owned(), "cannot be empty".to_owned()));
+      }
+    }
+    Ok(Entry::new_with_credential(Arc::new(Cred {
+      service: service.to_owned(),
+      user: user.to_owned(),
+      target: mods.get("target").cloned(),
+      label: mods.get("label").cloned(),
+      item: None,
+    })))
+  }
+
+  /// Unlike the store's own search, nothing is unlocked, see [search].
+  fn search(&self, spec: &HashMap<&str, &str>) -> Result<Vec<Entry>> {
+    Ok(
+      search(spec)?
+        .into_iter()
+        .map(|(entry, _, _)| entry)
+        .collect(),
+    )
+  }
+
+  fn as_any(&self) -> &dyn std::any::Any {
+    self
+  }
+
+  fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(self, f)
+  }
+}
+
+/// A Secret Service credential, looked up on the shared connection on every call.
+///
+/// The handles of an `Ambiguous` error are pinned to one of the matching
+/// items, and keep the service, user and target it had when it was found.
+#[derive(Debug)]
+pub struct Cred {
+  service: String,
+  user: String,
+  target: Option<String>,
+  label: Option<String>,
+  item: Option<Path<'static>>,
+}
+
+impl Cred {
+  /// The credential pinned to `item`, or `None` if it lacks a service or username.
+  fn pinned(item: &Item<'_>) -> Result<Option<Self>> {
+    let mut attributes = item.get_attributes().map_err(decode_error)?;
+    let (Some(service), Some(user)) = (attributes.remove("service"), attributes.remove("username"))
+    else {
+      return Ok(None);
+    };
+    Ok(Some(Cred {
+      service,
+      user,
+      target: attributes.remove("target"),
+      label: None,
+      item: Some(item.path.clone()),
+    }))
+  }
+
+  /// The collection the item is written to, if not the default one.
+  pub fn target(&self) -> Option<&str> {
+    self.target.as_deref()
+  }
+
+  /// The path of the item this credential is pinned to, if it is.
+  pub fn item_path(&self) -> Option<String> {
+    self.item.as_ref().map(ToString::to_string)
+  }
+
+  /// The attributes that find the item, as the store searches for it.
+  fn search_spec(&self) -> HashMap<&str, &str> {
+    let mut spec = HashMap::from([
+      ("service", self.service.as_str()),
+      ("username", self.user.as_str()),
+    ]);
+    if let Some(target) = &self.target {
+      spec.insert("target", target);
+    }
+    spec
+  }
+
+  /// Find the item of this credential without unlocking it, and tell whether it is locked.
+  ///
+  /// Returns a [NoEntry](Error::NoEntry) error if there is none, and an
+  /// [Ambiguous](Error::Ambiguous) error with a handle pinned to each item
+  /// if there are several.
+  fn find(&self) -> Result<(Path<'static>, bool)> {
+    if let Some(path) = &self.item {
+      return with_connection(|ss| {
+        let locked = Item::new(ss, path.clone())
+          .is_locked()
+          .map_err(decode_error)?;
+        Ok((path.clone(), locked))
+      });
+    }
+    let spec = self.search_spec();
+    with_connection(|ss| {
+      let found = ss.search_items(spec.clone()).map_err(decode_error)?;
+      match (found.unlocked.as_slice(), found.locked.as_slice()) {
+        ([], []) => Err(Error::NoEntry),
+        ([item], []) => Ok((item.path.clone(), false)),
+        ([], [item]) => Ok((item.path.clone(), true)),
+        _ => {
+          let mut entries = Vec::new();
+          for item in found.unlocked.iter().chain(&found.locked) {
+            if let Some(cred) = Cred::pinned(item)? {
+              entries.push(Entry::new_with_credential(Arc::new(cred)));
+            }
+          }
+          Err(Error::Ambiguous(entries))
+        }
+      }
+    })
+  }
+
+  /// Find the item of this credential and unlock it if needed.
+  ///
+  /// The unlock prompt runs on a connection of its own, so other calls go on
+  /// while it waits for the user.
+  fn unlocked_item(&self) -> Result<Path<'static>> {
+    let (path, locked) = self.find()?;
+    if locked {
+      unlock(&[&path], None)?;
+    }
+    Ok(path)
+  }
+
+  /// Run `op` with the unlocked item of this credential.
+  fn with_item<T>(&self, op: impl Fn(&Item<'_>) -> Result<T>) -> Result<T> {
+    let path = self.unlocked_item()?;
+    with_connection(|ss| op(&Item::new(ss, path.clone())))
+  }
+
+  /// Create the item of this credential in its target collection, creating
+  /// the collection first if there is none with that label.
+  fn create(&self, secret: &[u8]) -> Result<()> {
+    let name = self.target.as_deref().unwrap_or("default");
+    let found = with_connection(|ss| {
+      find_collection(ss, name)?
+        .map(|collection| {
+          let locked = collection.is_locked().map_err(decode_error)?;
+          Ok((collection.path, locked))
+        })
+        .transpose()
+    })?;
+    let path = match found {
+      Some((path, locked)) => {
+        if locked {
+          let ss = prompt_connection(None)?;
+          Collection::new(&ss, path.clone())
+            .unlock()
+            .map_err(decode_error)?;
+        }
+        path
+      }
+      None if name == "default" => {
+        return Err(decode_error(dbus_secret_service::Error::NoResult));
+      }
+      None => {
+        // Creating a collection prompts the user for its password.
+        let ss = prompt_connection(None)?;
+        ss.create_collection(name, "").map_err(decode_error)?.path
+      }
+    };
+    let label = match &self.label {
+      Some(label) => label.clone(),
+      None => format!("keyring:{}@{}", self.user, self.service),
+    };
+    // Creating an item may prompt the user to confirm, and needs an
+    // encrypted session to send the secret.
+    let ss = SecretService::connect(EncryptionType::Dh).map_err(decode_error)?;
+    Collection::new(&ss, path)
+      .create_item(&label, self.search_spec(), secret, true, CONTENT_TYPE)
+      .map_err(decode_error)?;
+    Ok(())
+  }
+
+  /// How many unlocked and locked items match, found without unlocking or reading any.
+  pub fn count_items(&self) -> Result<(usize, usize)> {
+    if self.item.is_some() {
+      return self
+        .find()
+        .map(|(_, locked)| if locked { (0, 1) } else { (1, 0) });
+    }
+    let spec = self.search_spec();
+    with_connection(|ss| {
+      let found = ss.search_items(spec.clone()).map_err(decode_error)?;
+      Ok((found.unlocked.len(), found.locked.len()))
+    })
+  }
+
+  /// Read the secret and attributes of the item with a single lookup.
+  ///
+  /// Returns `None` if the item is locked, missing or ambiguous, for the
+  /// store to unlock it or report the error.
+  pub(crate) fn read(&self) -> Result<Option<Contents>> {
+    if self.item.is_some() {
+      return Ok(None);
+    }
+    let spec = self.search_spec();
+    with_connection(|ss| {
+      let found = ss.search_items(spec.clone()).map_err(decode_error)?;
+      let ([item], []) = (found.unlocked.as_slice(), found.locked.as_slice()) else {
+        return Ok(None);
+      };
+      let attributes = item.get_attributes().map_err(decode_error)?;
+      // The item was locked or deleted since it was found.
+      Ok(read_secret(item)?.map(|secret| (secret, attributes)))
+    })
+  }
+
+  /// The attributes of every matching item, read without unlocking any.
+  pub(crate) fn peek_attributes(&self) -> Result<Vec<HashMap<String, String>>> {
+    if let Some(path) = &self.item {
+      return with_connection(|ss| {
+        Ok(vec![
+          Item::new(ss, path.clone())
+            .get_attributes()
+            .map_err(decode_error)?,
+        ])
+      });
+    }
+    let spec = self.search_spec();
+    with_connection(|ss| {
+      let found = ss.search_items(spec.clone()).map_err(decode_error)?;
+      found
+        .unlocked
+        .iter()
+        .chain(&found.locked)
+        .map(|item| item.get_attributes().map_err(decode_error))
+        .collect()
+    })
+  }
+
+  /// The label of the item.
+  pub fn get_label(&self) -> Result<String> {
+    self.with_item(|item| item.get_label().map_err(decode_error))
+  }
+
+  /// Update the label of the item.
+  pub fn set_label(&self, label: &str) -> Result<()> {
+    self.with_item(|item| item.set_label(label).map_err(decode_error))
+  }
+}
+
+impl CredentialApi for Cred {
+  fn set_secret(&self, secret: &[u8]) -> Result<()> {
+    match self.with_item(|item| item.set_secret(secret, CONTENT_TYPE).map_err(decode_error)) {
+      Err(Error::NoEntry) => self.create(secret),
+      result => result,
+    }
+  }
+
+  fn get_secret(&self) -> Result<Vec<u8>> {
+    self.with_item(|item| item.get_secret().map_err(decode_error))
+  }
+
+  fn get_attributes(&self) -> Result<HashMap<String, String>> {
+    self.with_item(|item| item.get_attributes().map_err(decode_error))
+  }
+
+  fn update_attributes(&self, attributes: &HashMap<&str, &str>) -> Result<()> {
+    if let Some(key) = attributes
+      .keys()
+      .find(|key| SPECIFIER_ATTRIBUTES.contains(key))
+    {
+      return Err(Error::Invalid(
+        key.to_string(),
+        "cannot be updated".to_owned(),
+      ));
+    }
+    self.with_item(|item| {
+      let mut updated = item.get_attributes().map_err(decode_error)?;
+      for (key, value) in attributes {
+        updated.insert(key.to_string(), value.to_string());
+      }
+      item
+        .set_attributes(
+          updated
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect(),
+        )
+        .map_err(decode_error)
+    })
+  }
+
+  fn delete_credential(&self) -> Result<()> {
+    let path = self.unlocked_item()?;
+    // Deleting an item may prompt the user to confirm.
+    let ss = prompt_connection(None)?;
+    Item::new(&ss, path).delete().map_err(decode_error)
+  }
+
+  fn get_credential(&self) -> Result<Option<Arc<Credential>>> {
+    let (path, _) = self.find()?;
+    if self.item.is_some() {
+      return Ok(None);
+    }
+    let found = with_connection(|ss| Cred::pinned(&Item::new(ss, path.clone())))?;
+    Ok(found.map(|cred| Arc::new(cred) as Arc<Credential>))
+  }
+
+  fn get_specifiers(&self) -> Option<(String, String)> {
+    Some((self.service.clone(), self.user.clone()))
+  }
+
+  fn as_any(&self) -> &dyn std::any::Any {
+    self
+  }
+
+  fn debug_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(self, f)
+  }
+}
+
+/// Search items by attributes without unlocking them.
+///
+/// Unlike the store's own search, which unlocks every match up front, each
+/// hit is returned as an entry built from the item's service, username and
+/// target, so it is only unlocked once its secret is used.
+pub(crate) fn search(spec: &HashMap<&str, &str>) -> Result<Vec<(Entry, Metadata, String)>> {
+  with_connection(|ss| {
+    let found = ss.search_items(spec.clone()).map_err(decode_error)?;
+    let mut results = Vec::with_capacity(found.unlocked.len() + found.locked.len());
+    for (item, locked) in found
+      .unlocked
+      .iter()
+      .map(|item| (item, false))
+      .chain(found.locked.iter().map(|item| (item, true)))
+    {
+      let metadata = describe(item, locked)?;
+      let (Some(service), Some(user)) = (
+        metadata.attributes.get("service"),
+        metadata.attributes.get("username"),
+      ) else {
+        continue;
+      };
+      let entry = Entry::new_with_credential(Arc::new(Cred {
+        service: service.clone(),
+        user: user.clone(),
+        target: metadata.target.clone(),
+        label: None,
+        item: None,
+      }));
+      results.push((entry, metadata, item.path.to_string()));
+    }
+    Ok(results)
+  })
+}
+
+/// Read the secrets and attributes of the credentials given as service, user and target.
+///
+/// Each credential is looked up without unlocking anything, the locked ones
+/// are unlocked with a single prompt, and all secrets are read over one
+/// encrypted session. Credentials that don't match exactly one item, or
+/// whose secret could not be read, are left as `None` to be read one by one.
+pub(crate) fn get_secrets(
+  specs: &[(&str, &str, Option<&str>)],
+) -> Result<Vec<Option<Result<Contents>>>> {
+  let mut results: Vec<Option<Result<Contents>>> = specs.iter().map(|_| None).collect();
+  let (mut paths, locked) = with_connection(|ss| {
+    let mut paths = Vec::with_capacity(specs.len());
+    let mut locked = Vec::new();
+    for (index, (service, user, target)) in specs.iter().enumerate() {
+      let mut spec = HashMap::from([("service", *service), ("username", *user)]);
+      if let Some(target) = target {
+        spec.insert("target", target);
+      }
+      let found = ss.search_items(spec).map_err(decode_error)?;
+      let item = match (found.unlocked.as_slice(), found.locked.as_slice()) {
+        ([item], []) => item,
+        ([], [item]) => {
+          locked.push(item.path.clone());
+          item
+        }
+        _ => continue,
+      };
+      // Attributes can be read while the item is still locked.
+      let attributes = item.get_attributes().map_err(decode_error)?;
+      paths.push((index, item.path.clone(), attributes));
+    }
+    Ok((paths, locked))
+  })?;
+  let unlocked = match locked.as_slice() {
+    [] => Ok(()),
+    locked => unlock(&locked.iter().map(|path| &**path).collect::<Vec<_>>(), None),
+  };
+  if let Err(err) = unlocked {
+    // A dismissed prompt fails every locked item rather than prompting again for each.
+    paths.retain(|(index, path, _)| {
+      if locked.contains(path) {
+        results[*index] = Some(Err(copy_error(&err)));
+        false
+      } else {
+        true
+      }
+    });
+  }
+  if paths.is_empty() {
+    return Ok(results);
+  }
+  let secrets = with_connection(|ss| {
+    paths
+      .iter()
+      .map(|(_, path, _)| read_secret(&Item::new(ss, path.clone())))
+      .collect::<Result<Vec<_>>>()
+  })?;
+  for ((index, _, attributes), secret) in paths.into_iter().zip(secrets) {
+    results[index] = secret.map(|secret| Ok((secret, attributes)));
+  }
+  Ok(results)
+}
+
+/// Read the secret of an unlocked `item`, or `None` if it was locked or
+/// deleted since it was found.
+fn read_secret(item: &Item<'_>) -> Result<Option<Vec<u8>>> {
+  match item.get_secret().map_err(decode_error) {
+    Ok(secret) => Ok(Some(secret)),
+    Err(err) if is_disconnected(&err) => Err(err),
+    Err(_) => Ok(None),
+  }
+}
+
+/// Keyring errors can't be cloned, so copy the kind and the message.
+pub(crate) fn copy_error(err: &Error) -> Error {
+  let source = || -> Box<dyn std::error::Error + Send + Sync> { err.to_string().into() };
+  match err {
+    Error::NoStorageAccess(_) => Error::NoStorageAccess(source()),
+    _ => Error::PlatformFailure(source()),
+  }
+}
+
+/// Unlock the items at `paths`, with a single prompt if the user must confirm.
+///
+/// Without a timeout, the prompt waits for the user indefinitely.
+pub(crate) fn unlock(paths: &[&str], prompt_timeout_seconds: Option<u32>) -> Result<()> {
+  let ss = prompt_connection(prompt_timeout_seconds)?;
+  let items = paths
+    .iter()
+    .map(|path| {
+      Path::new(path.to_string())
+        .map(|path| Item::new(&ss, path))
+        .map_err(|err| Error::PlatformFailure(err.into()))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  ss.unlock_all(&items.iter().collect::<Vec<_>>())
+    .map_err(decode_error)
+}
+
+/// A connection of its own for calls that may prompt the user, which is
+/// closed afterwards.
+///
+/// A prompt can stay up for as long as the user leaves it, so it must not
+/// hold the shared connection every other Secret Service call waits for.
+fn prompt_connection(prompt_timeout_seconds: Option<u32>) -> Result<SecretService> {
+  match prompt_timeout_seconds {
+    Some(seconds) => {
+      SecretService::connect_with_max_prompt_timeout(EncryptionType::Plain, seconds.into())
+    }
+    None => SecretService::connect(EncryptionType::Plain),
+  }
+  .map_err(decode_error)
+}
+
+/// Read the metadata of the item at `path` without unlocking it.
+pub(crate) fn describe_path(path: &str) -> Result<Metadata> {
+  let path = Path::new(path.to_owned()).map_err(|err| Error::Invalid("path".to_owned(), err))?;
+  with_connection(|ss| {
+    let item = Item::new(ss, path.clone());
+    let locked = item.is_locked().map_err(decode_error)?;
+    describe(&item, locked)
+  })
+}
+
+fn describe(item: &Item<'_>, locked: bool) -> Result<Metadata> {
+  let attributes = item.get_attributes().map_err(decode_error)?;
+  Ok(Metadata {
+    target: attributes.get("target").cloned(),
+    label: Some(item.get_label().map_err(decode_error)?),
+    created: Some(item.get_created().map_err(decode_error)? as i64 * 1000),
+    modified: Some(item.get_modified().map_err(decode_error)? as i64 * 1000),
+    attributes,
+    locked,
+  })
+}
+
+/// The collections of the Secret Service, without unlocking them.
+pub(crate) fn list_collections() -> Result<Vec<CollectionInfo>> {
+  with_connection(|ss| {
+    let default = alias_path(ss, "default")?;
+    ss.get_all_collections()
+      .map_err(decode_error)?
+      .iter()
+      .map(|collection| describe_collection(collection, default.as_ref()))
+      .collect()
+  })
+}
+
+/// Create a collection labeled `label`, with `alias` pointing to it.
+///
+/// Fails with an [Invalid](Error::Invalid) error if a collection has that
+/// label already: the store looks collections up by label, so a second one
+/// would never be written to.
+pub(crate) fn create_collection(label: &str, alias: Option<&str>) -> Result<CollectionInfo> {
+  if label.is_empty() {
+    return Err(Error::Invalid(
+      "label".to_owned(),
+      "cannot be empty".to_owned(),
+    ));
+  }
+  if with_connection(|ss| Ok(find_collection(ss, label)?.is_some()))? {
+    return Err(Error::Invalid(
+      "label".to_owned(),
+      "is the label of an existing collection".to_owned(),
+    ));
+  }
+  // Creating a collection prompts the user for its password.
+  let ss = prompt_connection(None)?;
+  let path = ss
+    .create_collection(label, alias.unwrap_or_default())
+    .map_err(decode_error)?
+    .path;
+  with_connection(|ss| {
+    let default = alias_path(ss, "default")?;
+    describe_collection(&Collection::new(ss, path.clone()), default.as_ref())
+  })
+}
+
+/// Delete the collection labeled `label` and its items.
+///
+/// Returns a [NoEntry](Error::NoEntry) error if there is none.
+pub(crate) fn delete_collection(label: &str) -> Result<()> {
+  let path = with_connection(|ss| {
+    let collection = find_collection(ss, label)?.ok_or(Error::NoEntry)?;
+    if alias_path(ss, "default")?.as_ref() == Some(&collection.path) {
+      return Err(Error::NotSupportedByStore(
+        "the default collection cannot be deleted".to_owned(),
+      ));
+    }
+    Ok(collection.path)
+  })?;
+  // Deleting a collection may prompt the user to confirm.
+  let ss = prompt_connection(None)?;
+  Collection::new(&ss, path).delete().map_err(decode_error)
+}
+
+/// The collection `alias` points to, if it is set.
+pub(crate) fn collection_by_alias(alias: &str) -> Result<Option<CollectionInfo>> {
+  with_connection(|ss| {
+    let default = alias_path(ss, "default")?;
+    alias_path(ss, alias)?
+      .map(|path| describe_collection(&Collection::new(ss, path), default.as_ref()))
+      .transpose()
+  })
+}
+
+/// Find a collection the way the store resolves a target: `default` is the
+/// default collection, any other name the first collection with that label.
+fn find_collection<'a>(ss: &'a SecretService, label: &str) -> Result<Option<Collection<'a>>> {
+  if label == "default" {
+    return Ok(alias_path(ss, "default")?.map(|path| Collection::new(ss, path)));
+  }
+  for collection in ss.get_all_collections().map_err(decode_error)? {
+    if collection.get_label().map_err(decode_error)? == label {
+      return Ok(Some(collection));
+    }
+  }
+  Ok(None)
+}
+
+/// The path of the collection `alias` points to, if it is set.
+fn alias_path(ss: &SecretService, alias: &str) -> Result<Option<Path<'static>>> {
+  match ss.get_collection_by_alias(alias) {
+    Ok(collection) => Ok(Some(collection.path)),
+    Err(dbus_secret_service::Error::NoResult) => Ok(None),
+    Err(err) => Err(decode_error(err)),
+  }
+}
+
+fn describe_collection(
+  collection: &Collection<'_>,
+  default: Option<&Path<'static>>,
+) -> Result<CollectionInfo> {
+  Ok(CollectionInfo {
+    label: collection.get_label().map_err(decode_error)?,
+    path: collection.path.to_string(),
+    locked: collection.is_locked().map_err(decode_error)?,
+    is_default: default == Some(&collection.path),
+  })
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use keyring_core::{Entry, Result};
+use napi_derive::napi;
+
+#[napi(object)]
+/// What identifies an entry, as returned by `toJSON` and taken by `fromSpecifier`.
+pub struct EntrySpecifier {
+  pub service: String,
+  pub user: String,
+  pub target: Option<String>,
+}
+
+/// Build an entry like `Entry.withTarget` does, or like `new Entry` without a target.
+pub(crate) fn build_entry(service: &str, user: &str, target: Option<&str>) -> Result<Entry> {
+  let Some(target) = target else {
+    return Entry::new(service, user);
+  };
+  let mut mods = HashMap::new();
+  #[cfg(target_os = "macos")]
+  mods.insert("keychain", target);
+  #[cfg(not(target_os = "macos"))]
+  mods.insert("target", target);
+  Entry::new_with_modifiers(service, user, &mods)
+}
+
+/// The target the credential of `entry` is written to, if the store records one.
+pub(crate) fn target_of(entry: &Entry) -> Option<String> {
+  let any = entry.as_any();
+  if let Some(cred) = any.downcast_ref::<crate::mock_store::Cred>() {
+    return cred.target().map(ToOwned::to_owned);
+  }
+  #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+  if let Some(cred) = any.downcast_ref::<crate::secret_service::Cred>() {
+    return cred.target().map(ToOwned::to_owned);
+  }
+  None
+}
+
+/// The specifier of an entry whose `target` is known, if the store can tell its service and user.
+pub(crate) fn specifier(entry: &Entry, target: Option<&str>) -> Option<EntrySpecifier> {
+  let (service, user) = entry.get_specifiers()?;
+  Some(EntrySpecifier {
+    service,
+    user,
+    target: target.map(ToOwned::to_owned),
+  })
+}
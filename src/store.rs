@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use keyring_core::{CredentialPersistence, CredentialStore, Error as KeyringCoreError};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::error::{ErrorCode, to_napi_error};
+
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Credential store backends that can be selected with `configureStore`.
+pub enum Backend {
+  /// The freedesktop Secret Service over D-Bus (Linux and BSD).
+  SecretService,
+  /// The Linux kernel keyutils keyrings.
+  Keyutils,
+  /// The macOS keychain.
+  Keychain,
+  /// The Windows Credential Manager.
+  Windows,
+  /// An in-process store without persistence, for tests.
+  Mock,
+}
+
+impl Backend {
+  fn name(&self) -> &'static str {
+    match self {
+      Backend::SecretService => "secret-service",
+      Backend::Keyutils => "keyutils",
+      Backend::Keychain => "keychain",
+      Backend::Windows => "windows",
+      Backend::Mock => "mock",
+    }
+  }
+}
+
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How long credentials written to a store survive.
+pub enum Persistence {
+  /// Only as long as the entry that wrote them.
+  EntryOnly,
+  /// Until the process exits.
+  ProcessOnly,
+  /// Until the user logs out.
+  UntilLogout,
+  /// Until the machine reboots.
+  UntilReboot,
+  /// Until they are deleted.
+  UntilDelete,
+  /// The store does not say.
+  Unspecified,
+}
+
+impl From<CredentialPersistence> for Persistence {
+  fn from(value: CredentialPersistence) -> Self {
+    match value {
+      CredentialPersistence::EntryOnly => Persistence::EntryOnly,
+      CredentialPersistence::ProcessOnly => Persistence::ProcessOnly,
+      CredentialPersistence::UntilLogout => Persistence::UntilLogout,
+      CredentialPersistence::UntilReboot => Persistence::UntilReboot,
+      CredentialPersistence::UntilDelete => Persistence::UntilDelete,
+      _ => Persistence::Unspecified,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct StoreInfo {
+  /// The active backend.
+  pub backend: Backend,
+  /// The vendor string reported by the store, naming its implementation.
+  pub vendor: String,
+  /// The id reported by the store, including its version.
+  pub id: String,
+  /// How long credentials written to the store survive.
+  pub persistence: Persistence,
+  /// Why the platform default store was not used, if a fallback happened.
+  pub fallback_reason: Option<String>,
+  /// The serial number of the keyutils keyring credentials are written to.
+  pub keyring_serial: Option<i32>,
+}
+
+#[napi(object)]
+pub struct StoreConfig {
+  /// The backend to use. Defaults to the platform default store.
+  pub backend: Option<Backend>,
+  /// Configuration passed to the backend when it is created.
+  pub options: Option<HashMap<String, String>>,
+}
+
+struct ActiveStore {
+  backend: Backend,
+  fallback_reason: Option<String>,
+}
+
+/// The store installed as the `keyring_core` default store, if any.
+///
+/// The lock also serializes store creation, so the default store is only
+/// created once even if entries are constructed concurrently.
+static ACTIVE_STORE: Mutex<Option<ActiveStore>> = Mutex::new(None);
+
+#[napi]
+/// Select the credential store used by entries created after this call.
+///
+/// Without a `backend`, the platform default store is used. If the requested
+/// backend cannot be created, an error is thrown and the current store is kept.
+///
+/// Entries created before this call keep using the previous store.
+pub fn configure_store(env: Env, config: Option<StoreConfig>) -> Result<(), ErrorCode> {
+  let (backend, options) = match config {
+    Some(config) => (config.backend, config.options.unwrap_or_default()),
+    None => (None, HashMap::new()),
+  };
+  let options = options
+    .iter()
+    .map(|(k, v)| (k.as_str(), v.as_str()))
+    .collect::<HashMap<_, _>>();
+  let mut active = ACTIVE_STORE
+    .lock()
+    .expect("Poisoned store lock, please report a bug");
+  let (store, info) = match backend {
+    Some(backend) => {
+      let store = create_store(backend, &options).map_err(to_napi_error)?;
+      (
+        store,
+        ActiveStore {
+          backend,
+          fallback_reason: None,
+        },
+      )
+    }
+    None if options.is_empty() => create_default_store().map_err(to_napi_error)?,
+    None => {
+      return Err(to_napi_error(KeyringCoreError::Invalid(
+        "options".to_owned(),
+        "require an explicit backend".to_owned(),
+      )));
+    }
+  };
+  install(&env, &mut active, store, info);
+  Ok(())
+}
+
+#[napi]
+/// Describe the credential store used by new entries.
+///
+/// The platform default store is set up first if no store is configured yet.
+pub fn get_store_info(env: Env) -> Result<StoreInfo, ErrorCode> {
+  ensure_default_store(&env).map_err(to_napi_error)?;
+  let active = ACTIVE_STORE
+    .lock()
+    .expect("Poisoned store lock, please report a bug");
+  let (Some(active), Some(store)) = (active.as_ref(), keyring_core::get_default_store()) else {
+    return Err(to_napi_error(KeyringCoreError::NoDefaultStore));
+  };
+  Ok(StoreInfo {
+    backend: active.backend,
+    vendor: store.vendor(),
+    id: store.id(),
+    persistence: store.persistence().into(),
+    fallback_reason: active.fallback_reason.clone(),
+    keyring_serial: keyring_serial(&store),
+  })
+}
+
+fn keyring_serial(store: &Arc<CredentialStore>) -> Option<i32> {
+  #[cfg(target_os = "linux")]
+  if let Some(store) = store
+    .as_any()
+    .downcast_ref::<crate::keyutils_store::Store>()
+  {
+    return store.keyring_serial();
+  }
+  let _ = store;
+  None
+}
+
+/// Install the platform default store unless a store is already configured.
+pub(crate) fn ensure_default_store(env: &Env) -> keyring_core::Result<()> {
+  let mut active = ACTIVE_STORE
+    .lock()
+    .expect("Poisoned store lock, please report a bug");
+  if active.is_none() {
+    let (store, info) = create_default_store()?;
+    install(env, &mut active, store, info);
+  }
+  Ok(())
+}
+
+/// The backend and store used by new entries, once [ensure_default_store] has run.
+pub(crate) fn active_store() -> keyring_core::Result<(Backend, Arc<CredentialStore>)> {
+  let active = ACTIVE_STORE
+    .lock()
+    .expect("Poisoned store lock, please report a bug");
+  match (active.as_ref(), keyring_core::get_default_store()) {
+    (Some(active), Some(store)) => Ok((active.backend, store)),
+    _ => Err(KeyringCoreError::NoDefaultStore),
+  }
+}
+
+fn install(
+  env: &Env,
+  active: &mut Option<ActiveStore>,
+  store: Arc<CredentialStore>,
+  info: ActiveStore,
+) {
+  if let Some(reason) = &info.fallback_reason {
+    // A failure to warn must not prevent the store from being used.
+    let _ = emit_fallback_warning(env, info.backend, reason);
+  }
+  keyring_core::set_default_store(store);
+  *active = Some(info);
+}
+
+fn emit_fallback_warning(env: &Env, backend: Backend, reason: &str) -> Result<()> {
+  let process: Object = env.get_global()?.get_named_property("process")?;
+  let emit_warning: Function<FnArgs<(String, String)>, ()> =
+    process.get_named_property("emitWarning")?;
+  let message = format!(
+    "Falling back to the {} credential store: {reason}",
+    backend.name()
+  );
+  emit_warning.apply(
+    process,
+    (message, "KeyringFallbackWarning".to_owned()).into(),
+  )
+}
+
+fn create_default_store() -> keyring_core::Result<(Arc<CredentialStore>, ActiveStore)> {
+  #[cfg(target_os = "linux")]
+  let (store, backend, fallback_reason) = {
+    let builder = crate::linux_credential_builder::LinuxCredentialBuilder::new()?;
+    (
+      builder.get_store(),
+      builder.backend(),
+      builder.fallback_reason().map(ToOwned::to_owned),
+    )
+  };
+  #[cfg(target_os = "macos")]
+  let (store, backend, fallback_reason) = (
+    create_store(Backend::Keychain, &HashMap::new())?,
+    Backend::Keychain,
+    None,
+  );
+  #[cfg(target_os = "windows")]
+  let (store, backend, fallback_reason) = (
+    create_store(Backend::Windows, &HashMap::new())?,
+    Backend::Windows,
+    None,
+  );
+  #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+  let (store, backend, fallback_reason) = (
+    create_store(Backend::SecretService, &HashMap::new())?,
+    Backend::SecretService,
+    None,
+  );
+  Ok((
+    store,
+    ActiveStore {
+      backend,
+      fallback_reason,
+    },
+  ))
+}
+
+fn create_store(
+  backend: Backend,
+  options: &HashMap<&str, &str>,
+) -> keyring_core::Result<Arc<CredentialStore>> {
+  match backend {
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    Backend::SecretService => Ok(crate::secret_service::Store::new_with_configuration(
+      options,
+    )?),
+    #[cfg(target_os = "linux")]
+    Backend::Keyutils => Ok(crate::keyutils_store::Store::new_with_configuration(
+      options,
+    )?),
+    #[cfg(target_os = "macos")]
+    Backend::Keychain => {
+      Ok(apple_native_keyring_store::keychain::Store::new_with_configuration(options)?)
+    }
+    #[cfg(target_os = "windows")]
+    Backend::Windows => Ok(windows_native_keyring_store::Store::new_with_configuration(
+      options,
+    )?),
+    Backend::Mock => {
+      if !options.is_empty() {
+        return Err(KeyringCoreError::NotSupportedByStore(
+          "The mock store has no options".to_owned(),
+        ));
+      }
+      Ok(crate::mock_store::Store::new())
+    }
+    #[allow(unreachable_patterns)]
+    _ => Err(KeyringCoreError::NotSupportedByStore(format!(
+      "The {} backend is not available on this platform",
+      backend.name()
+    ))),
+  }
+}
@@ -0,0 +1,125 @@
+use keyring_core::{Error, Result};
+use napi_derive::napi;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// How much of a URL goes into the service of `Entry.fromUrl`.
+pub enum UrlGranularity {
+  /// Scheme, host and port, e.g. `https://example.com:8443`.
+  #[default]
+  Origin,
+  /// The host alone, shared by every scheme and port, e.g. `example.com`.
+  Host,
+  /// Scheme, host, port and path, e.g. `https://example.com/org/repo`.
+  Path,
+}
+
+#[napi(object)]
+pub struct UrlOptions {
+  /// The user, for URLs without one. A user in the URL takes precedence.
+  pub user: Option<String>,
+  /// Defaults to `'origin'`.
+  pub granularity: Option<UrlGranularity>,
+  /// The target to create the entry with, as in `Entry.withTarget`.
+  pub target: Option<String>,
+}
+
+/// Turn `url` into the service and user of its entry.
+///
+/// Scheme and host are lowercased, default ports, query, fragment and any
+/// password are dropped, and the path loses its empty segments, so every
+/// spelling of the same location gives the same service.
+pub(crate) fn specifiers(url: &str, options: Option<&UrlOptions>) -> Result<(String, String)> {
+  let invalid = |reason: &str| Error::Invalid("url".to_owned(), reason.to_owned());
+  let (scheme, rest) = url
+    .trim()
+    .split_once("://")
+    .ok_or_else(|| invalid("has no scheme"))?;
+  let scheme = scheme.to_ascii_lowercase();
+  if scheme.is_empty()
+    || !scheme
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+  {
+    return Err(invalid("has an invalid scheme"));
+  }
+  let rest = rest.split(['?', '#']).next().unwrap_or_default();
+  let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+  let (userinfo, host_port) = match authority.rsplit_once('@') {
+    Some((userinfo, host_port)) => (Some(userinfo), host_port),
+    None => (None, authority),
+  };
+  let (host, port) = split_port(host_port).ok_or_else(|| invalid("has an invalid port"))?;
+  let host = host.to_ascii_lowercase();
+  if host.is_empty() {
+    return Err(invalid("has no host"));
+  }
+  let port = port.filter(|port| Some(*port) != default_port(&scheme));
+  let user = match userinfo.map(|userinfo| userinfo.split(':').next().unwrap_or_default()) {
+    Some(user) if !user.is_empty() => decode(user).ok_or_else(|| invalid("has an invalid user"))?,
+    _ => options
+      .and_then(|options| options.user.clone())
+      .ok_or_else(|| invalid("has no user, pass one in the options"))?,
+  };
+  let origin = match port {
+    Some(port) => format!("{scheme}://{host}:{port}"),
+    None => format!("{scheme}://{host}"),
+  };
+  let service = match options
+    .and_then(|options| options.granularity)
+    .unwrap_or_default()
+  {
+    UrlGranularity::Origin => origin,
+    UrlGranularity::Host => host,
+    UrlGranularity::Path => path
+      .split('/')
+      .filter(|segment| !segment.is_empty())
+      .fold(origin, |service, segment| service + "/" + segment),
+  };
+  Ok((service, user))
+}
+
+/// Split off the port of `host[:port]`, keeping IPv6 hosts in brackets.
+fn split_port(host_port: &str) -> Option<(&str, Option<u16>)> {
+  let colon = match host_port.rfind(']') {
+    Some(bracket) => host_port[bracket..].find(':').map(|colon| bracket + colon),
+    None => host_port.rfind(':'),
+  };
+  let Some(colon) = colon else {
+    return Some((host_port, None));
+  };
+  let (host, port) = (&host_port[..colon], &host_port[colon + 1..]);
+  if port.is_empty() {
+    return Some((host, None));
+  }
+  port.parse().ok().map(|port| (host, Some(port)))
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+  match scheme {
+    "http" | "ws" => Some(80),
+    "https" | "wss" => Some(443),
+    "ftp" => Some(21),
+    "ssh" | "git+ssh" => Some(22),
+    "git" => Some(9418),
+    _ => None,
+  }
+}
+
+/// Percent-decode a URL component.
+fn decode(component: &str) -> Option<String> {
+  let bytes = component.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+  while index < bytes.len() {
+    if bytes[index] == b'%' {
+      let hex = component.get(index + 1..index + 3)?;
+      decoded.push(u8::from_str_radix(hex, 16).ok()?);
+      index += 3;
+    } else {
+      decoded.push(bytes[index]);
+      index += 1;
+    }
+  }
+  String::from_utf8(decoded).ok()
+}
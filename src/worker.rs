@@ -0,0 +1,306 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use napi::bindgen_prelude::*;
+use napi::{JsDeferred, sys};
+
+use crate::error::{ErrorCode, KeyringError, into_rejection};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+type Resolver<T> = Box<dyn FnOnce(Env) -> Result<T> + Send>;
+
+const QUEUED: u8 = 0;
+const RUNNING: u8 = 1;
+const DONE: u8 = 2;
+const CANCELLED: u8 = 3;
+
+/// How many workers can be left behind in a cancelled task at a time, see [WorkerTask].
+const MAX_LEFT_BEHIND: usize = 4;
+
+/// A [Task] run on the keyring worker thread instead of the libuv thread pool.
+///
+/// Store calls can wait on the user indefinitely, e.g. for a Secret Service
+/// unlock prompt, and a libuv thread parked on a prompt is not available to
+/// `fs`, `dns` and other async work. The worker runs one task at a time,
+/// which every store serializes anyway.
+///
+/// The promise rejects with an `Aborted` error as soon as the signal is
+/// aborted, and with a `Timeout` error once the timeout has elapsed, whether
+/// or not the task has started. Store calls and prompts cannot be cancelled,
+/// so a task that is still running is left behind to finish on its own, and
+/// a new worker takes over the queue: the tasks after it can then run while
+/// it does. Once [MAX_LEFT_BEHIND] workers are left behind, the queue waits
+/// for the running task instead, so stuck calls cannot pile up threads.
+pub struct WorkerTask<T: Task> {
+  task: T,
+  signal: Option<AbortSignal>,
+  timeout_ms: Option<u32>,
+}
+
+impl<T: Task> WorkerTask<T> {
+  pub fn with_optional_signal(task: T, signal: Option<AbortSignal>) -> Self {
+    Self {
+      task,
+      signal,
+      timeout_ms: None,
+    }
+  }
+
+  /// Reject the task if it has not finished after `timeout_ms` milliseconds.
+  pub fn with_timeout(self, timeout_ms: Option<u32>) -> Self {
+    Self { timeout_ms, ..self }
+  }
+}
+
+impl<T: Task + 'static> ToNapiValue for WorkerTask<T> {
+  unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+    let WorkerTask {
+      mut task,
+      signal,
+      timeout_ms,
+    } = val;
+    let env = Env::from_raw(env);
+    let (deferred, promise) = env.create_deferred::<T::JsValue, Resolver<T::JsValue>>()?;
+    let pending = Arc::new(Pending {
+      deferred: Mutex::new(Some(deferred)),
+      state: AtomicU8::new(QUEUED),
+    });
+    if let Some(signal) = signal {
+      let pending = pending.clone();
+      signal.on_abort(move || pending.cancel(ErrorCode::Aborted, "The operation was aborted"));
+    }
+    let timer = timeout_ms.map(|timeout_ms| {
+      let pending = pending.clone();
+      Timer::start(Duration::from_millis(timeout_ms.into()), move || {
+        pending.cancel(
+          ErrorCode::Timeout,
+          &format!("The operation timed out after {timeout_ms} ms"),
+        )
+      })
+    });
+    submit(Box::new(move || {
+      // Dropped once the task finishes or is skipped, which clears the timer.
+      let _timer = timer;
+      if !pending.start() {
+        return;
+      }
+      let output = task.compute();
+      pending.finish(Box::new(move |env: Env| match output {
+        Ok(output) => task.resolve(env, output),
+        Err(err) => task.reject(env, err),
+      }));
+    }));
+    unsafe { ToNapiValue::to_napi_value(env.raw(), promise) }
+  }
+}
+
+/// A task's promise, settled by whichever of the task, its signal and its timer comes first.
+struct Pending<T: ToNapiValue> {
+  deferred: Mutex<Option<JsDeferred<T, Resolver<T>>>>,
+  state: AtomicU8,
+}
+
+impl<T: ToNapiValue> Pending<T> {
+  /// Whether the task should run, i.e. it was not cancelled while queued.
+  fn start(&self) -> bool {
+    self
+      .state
+      .compare_exchange(QUEUED, RUNNING, Ordering::AcqRel, Ordering::Acquire)
+      .is_ok()
+  }
+
+  fn finish(&self, resolver: Resolver<T>) {
+    self.state.store(DONE, Ordering::Release);
+    self.settle(resolver);
+  }
+
+  /// Reject with `code`, unless the promise is already settled.
+  fn cancel(&self, code: ErrorCode, message: &str) {
+    let error = KeyringError::new(code, message.to_owned());
+    if !self.settle(Box::new(move |env: Env| Err(into_rejection(&env, error)))) {
+      return;
+    }
+    if let Err(RUNNING) =
+      self
+        .state
+        .compare_exchange(QUEUED, CANCELLED, Ordering::AcqRel, Ordering::Acquire)
+    {
+      replace_worker();
+    }
+  }
+
+  /// Settle the promise with `resolver`, returning false if it was already settled.
+  fn settle(&self, resolver: Resolver<T>) -> bool {
+    let deferred = self
+      .deferred
+      .lock()
+      .expect("Poisoned task lock, please report a bug")
+      .take();
+    match deferred {
+      Some(deferred) => {
+        deferred.resolve(resolver);
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+struct Queue {
+  sender: Sender<Job>,
+  receiver: Mutex<Receiver<Job>>,
+  /// Incremented when a worker is left behind in a task, which then exits.
+  generation: AtomicUsize,
+  /// How many workers are left behind and have not exited yet.
+  left_behind: AtomicUsize,
+}
+
+fn queue() -> &'static Queue {
+  static QUEUE: OnceLock<Queue> = OnceLock::new();
+
+  QUEUE.get_or_init(|| {
+    let (sender, receiver) = channel();
+    spawn_worker(0);
+    Queue {
+      sender,
+      receiver: Mutex::new(receiver),
+      generation: AtomicUsize::new(0),
+      left_behind: AtomicUsize::new(0),
+    }
+  })
+}
+
+fn spawn_worker(generation: usize) {
+  std::thread::Builder::new()
+    .name("keyring-worker".to_owned())
+    .spawn(move || {
+      let queue = queue();
+      while queue.generation.load(Ordering::Acquire) == generation {
+        let job = queue
+          .receiver
+          .lock()
+          .expect("Poisoned worker lock, please report a bug")
+          .recv();
+        match job {
+          Ok(job) => job(),
+          Err(_) => return,
+        }
+      }
+      queue.left_behind.fetch_sub(1, Ordering::AcqRel);
+    })
+    .expect("Failed to start the keyring worker thread");
+}
+
+/// Start a new worker for the queue, leaving the current one in its task,
+/// unless [MAX_LEFT_BEHIND] workers are left behind already.
+fn replace_worker() {
+  let queue = queue();
+  let reserved = queue
+    .left_behind
+    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+      (count < MAX_LEFT_BEHIND).then_some(count + 1)
+    });
+  if reserved.is_ok() {
+    let generation = queue.generation.fetch_add(1, Ordering::AcqRel) + 1;
+    spawn_worker(generation);
+  }
+}
+
+/// Queue `job` on the keyring worker thread, starting it on first use.
+fn submit(job: Job) {
+  queue()
+    .sender
+    .send(job)
+    .expect("The keyring worker thread stopped, please report a bug");
+}
+
+/// A task timeout, cleared when dropped.
+///
+/// Every timeout is fired by the same thread, started on first use.
+struct Timer(u64);
+
+#[derive(Default)]
+struct Timers {
+  next_id: u64,
+  /// The deadline and id of every timer, soonest first. Cleared timers stay
+  /// until their deadline and are skipped then.
+  deadlines: BinaryHeap<Reverse<(Instant, u64)>>,
+  callbacks: HashMap<u64, Box<dyn FnOnce() + Send>>,
+}
+
+impl Timer {
+  /// Run `callback` on the timer thread after `timeout`, unless the timer is dropped first.
+  fn start(timeout: Duration, callback: impl FnOnce() + Send + 'static) -> Self {
+    let (timers, changed) = timers();
+    let mut state = lock_timers(timers);
+    let id = state.next_id;
+    state.next_id += 1;
+    state
+      .deadlines
+      .push(Reverse((Instant::now() + timeout, id)));
+    state.callbacks.insert(id, Box::new(callback));
+    changed.notify_one();
+    Timer(id)
+  }
+}
+
+impl Drop for Timer {
+  fn drop(&mut self) {
+    lock_timers(&timers().0).callbacks.remove(&self.0);
+  }
+}
+
+fn timers() -> &'static (Mutex<Timers>, Condvar) {
+  static TIMERS: OnceLock<(Mutex<Timers>, Condvar)> = OnceLock::new();
+
+  TIMERS.get_or_init(|| {
+    std::thread::Builder::new()
+      .name("keyring-timeout".to_owned())
+      .spawn(run_timers)
+      .expect("Failed to start the keyring timer thread");
+    Default::default()
+  })
+}
+
+fn run_timers() {
+  let (timers, changed) = timers();
+  let mut state = lock_timers(timers);
+  loop {
+    let now = Instant::now();
+    state = match state.deadlines.peek() {
+      None => changed
+        .wait(state)
+        .expect("Poisoned timer lock, please report a bug"),
+      Some(Reverse((deadline, _))) if *deadline > now => {
+        let timeout = *deadline - now;
+        changed
+          .wait_timeout(state, timeout)
+          .expect("Poisoned timer lock, please report a bug")
+          .0
+      }
+      Some(&Reverse((_, id))) => {
+        state.deadlines.pop();
+        match state.callbacks.remove(&id) {
+          Some(callback) => {
+            // The callback settles a promise, which takes other locks.
+            drop(state);
+            callback();
+            lock_timers(timers)
+          }
+          None => state,
+        }
+      }
+    };
+  }
+}
+
+fn lock_timers(timers: &Mutex<Timers>) -> MutexGuard<'_, Timers> {
+  timers
+    .lock()
+    .expect("Poisoned timer lock, please report a bug")
+}